@@ -0,0 +1,151 @@
+use schedual::mask::ScheduleMask;
+use schedual::plan::Plan;
+use schedual::solver::{self, SearchStats};
+use schedual::term::TermFolder;
+use schedual::{exclusion, ClassBank, Crn, Schedule, SmallClass};
+use std::path::Path;
+use std::time::Instant;
+
+// Compares the search strategies on a real bank, run with `cargo bench --bench solver`.
+// Reads `fall2023/data.json` and `plans/fall2023.toml` unless SCHEDUAL_DATA_DIR or
+// SCHEDUAL_BENCH_PLAN point somewhere else.
+
+fn main() -> anyhow::Result<()> {
+    let folder = TermFolder::new(
+        std::env::var("SCHEDUAL_DATA_DIR").unwrap_or_else(|_| "fall2023".to_owned()),
+    );
+    let plan =
+        std::env::var("SCHEDUAL_BENCH_PLAN").unwrap_or_else(|_| "plans/fall2023.toml".to_owned());
+
+    if !folder.data().exists() {
+        println!(
+            "Skipping, no bank at {} (set SCHEDUAL_DATA_DIR)",
+            folder.data().display()
+        );
+        return Ok(());
+    }
+
+    let mut bank = folder.load_bank()?;
+    let plan = Plan::load(Path::new(&plan))?;
+    exclusion::apply(&mut bank, &plan.exclusions);
+
+    bench("plan", &bank, &plan);
+    bench(
+        "plan without constraints",
+        &bank,
+        &Plan {
+            constraints: Vec::new(),
+            ..plan.clone()
+        },
+    );
+
+    Ok(())
+}
+
+fn bench(name: &str, bank: &ClassBank, plan: &Plan) {
+    let prepared = plan.prepare(bank);
+
+    // Before pruning, so propagation has the conflicts to cut down on
+    for (idx, variant) in prepared.linked.iter().enumerate() {
+        let data = &variant.groups;
+
+        println!();
+        println!(
+            "{name} (variant {idx}): {} groups, {} sections",
+            data.len(),
+            data.iter().map(|group| group.len()).sum::<usize>()
+        );
+
+        run("bruteforce", data, |data, callback| {
+            solver::bruteforce_stats(data, &mut &mut *callback)
+        });
+        run("propagate", data, |data, callback| {
+            solver::propagate_schedules(data, &mut &mut *callback)
+        });
+    }
+
+    println!();
+    let start = Instant::now();
+    let solution = plan.solve(bank, plan.results, 1);
+    println!(
+        "  {:<12} {:>12} best {:>10.3}ms",
+        "top",
+        solution.schedules.len(),
+        start.elapsed().as_secs_f64() * 1000.0
+    );
+
+    let schedules = prepared
+        .linked
+        .iter()
+        .flat_map(|variant| variant.groups.iter().flatten())
+        .map(|class| &class.schedule)
+        .collect::<Vec<_>>();
+    overlaps(&schedules);
+}
+
+// Checks every pair of sections for overlaps with both encodings
+fn overlaps(schedules: &[&Schedule]) {
+    let start = Instant::now();
+    // Meetings off the grid have no mask, only the rest are compared
+    let (schedules, masks): (Vec<&Schedule>, Vec<ScheduleMask>) = schedules
+        .iter()
+        .filter_map(|it| Some((*it, ScheduleMask::new(it)?)))
+        .unzip();
+    let convert = start.elapsed().as_secs_f64() * 1000.0;
+
+    let start = Instant::now();
+    let intervals = pairs(&schedules, |a, b| a.overlaps(&[b]));
+    let interval_time = start.elapsed().as_secs_f64() * 1000.0;
+
+    let start = Instant::now();
+    let bitmasks = pairs(&masks.iter().collect::<Vec<_>>(), |a, b| a.overlaps(&[b]));
+    let mask_time = start.elapsed().as_secs_f64() * 1000.0;
+
+    let start = Instant::now();
+    for mask in &masks {
+        std::hint::black_box(mask.to_schedule());
+    }
+    let back = start.elapsed().as_secs_f64() * 1000.0;
+
+    println!(
+        "  {:<12} {:>12} pairs {:>10} overlap {:>10.3}ms",
+        "intervals",
+        schedules.len() * schedules.len(),
+        intervals,
+        interval_time
+    );
+    println!(
+        "  {:<12} {:>12} pairs {:>10} overlap {:>10.3}ms ({convert:.3}ms to build, {back:.3}ms back)",
+        "bitmasks",
+        masks.len() * masks.len(),
+        bitmasks,
+        mask_time
+    );
+}
+
+fn pairs<T>(items: &[&T], overlaps: impl Fn(&T, &T) -> bool) -> usize {
+    let mut count = 0;
+    for a in items {
+        for b in items {
+            count += std::hint::black_box(overlaps(a, b)) as usize;
+        }
+    }
+    count
+}
+
+fn run(
+    name: &str,
+    data: &[Vec<SmallClass>],
+    strategy: impl Fn(&[Vec<SmallClass>], &mut dyn FnMut(&[Crn], &[&Schedule])) -> SearchStats,
+) {
+    let start = Instant::now();
+    let mut found = 0;
+    let stats = strategy(data, &mut |_, _| found += 1);
+
+    println!(
+        "  {name:<12} {:>12} nodes {:>10} schedules {:>10.3}ms",
+        stats.nodes,
+        found,
+        start.elapsed().as_secs_f64() * 1000.0
+    );
+}
@@ -0,0 +1,613 @@
+use crate::exclusion::{self, Exclusion};
+use crate::{
+    parse_date, Class, ClassBank, CreditHours, Crn, CrossList, Days, Enrollment, Faculty, Schedule,
+    Session, Time,
+};
+use anyhow::{bail, Context};
+use chrono::NaiveDate;
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::PathBuf;
+
+// Mirrors the section objects returned by Banner's `searchResults` endpoint. Nearly everything is
+// optional because Banner sends `null` for fields it has no value for, `into_class` decides which
+// of them a class can't do without.
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannerSection {
+    pub campus_description: Option<String>,
+    pub course_reference_number: Option<String>,
+    pub course_number: Option<String>,
+    pub course_title: Option<String>,
+    pub credit_hour_high: Option<u64>,
+    pub credit_hour_low: Option<u64>,
+    pub credit_hours: Option<u64>,
+    // Only numeric cross lists are kept
+    pub cross_list: Option<Value>,
+    pub cross_list_available: Option<i64>,
+    pub cross_list_capacity: Option<u64>,
+    pub cross_list_count: Option<u64>,
+    pub enrollment: Option<u64>,
+    pub maximum_enrollment: Option<u64>,
+    pub seats_available: Option<i64>,
+    pub wait_count: Option<u64>,
+    pub wait_capacity: Option<u64>,
+    pub wait_available: Option<i64>,
+    pub faculty: Option<Vec<BannerFaculty>>,
+    pub instructional_method_description: Option<String>,
+    pub meetings_faculty: Option<Vec<BannerMeetingFaculty>>,
+    pub open_section: Option<bool>,
+    pub part_of_term_description: Option<String>,
+    pub schedule_type_description: Option<String>,
+    pub sequence_number: Option<String>,
+    pub special_approval_description: Option<String>,
+    pub subject_course: Option<String>,
+    pub subject_description: Option<String>,
+    pub term_desc: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannerFaculty {
+    pub display_name: Option<String>,
+    pub email_address: Option<String>,
+    pub primary_indicator: Option<bool>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannerMeetingFaculty {
+    pub meeting_time: Option<BannerMeetingTime>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannerMeetingTime {
+    pub begin_time: Option<String>,
+    pub end_time: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub monday: Option<bool>,
+    pub tuesday: Option<bool>,
+    pub wednesday: Option<bool>,
+    pub thursday: Option<bool>,
+    pub friday: Option<bool>,
+    pub saturday: Option<bool>,
+    pub sunday: Option<bool>,
+    pub building: Option<String>,
+    pub building_description: Option<String>,
+    pub room: Option<String>,
+    pub meeting_type_description: Option<String>,
+}
+
+/// Why a single section could not be converted
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct SectionError {
+    /// Position of the section in the raw data
+    pub index: usize,
+    pub crn: Option<String>,
+    /// Path of the offending field in the Banner payload (ex: `meetingsFaculty[0].meetingTime.endDate`)
+    pub field: String,
+    pub reason: String,
+}
+
+impl Display for SectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.crn {
+            Some(crn) => write!(f, "CRN {crn}")?,
+            None => write!(f, "Section #{}", self.index)?,
+        }
+
+        write!(f, ", field `{}`: {}", self.field, self.reason)
+    }
+}
+
+impl std::error::Error for SectionError {}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Strictness {
+    /// Any missing or malformed field skips the section
+    #[default]
+    Strict,
+    /// Fields a class can do without are defaulted, only sections without a usable CRN or subject are skipped
+    Lenient,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Conversion {
+    pub bank: ClassBank,
+    /// Sections that were left out of the bank
+    pub errors: Vec<SectionError>,
+    /// Fields that were defaulted to keep a section, only filled in lenient mode
+    pub defaulted: Vec<SectionError>,
+    pub total: usize,
+}
+
+/// Data quality summary of a conversion, meant to be written next to the class bank
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConversionReport {
+    pub strictness: Strictness,
+    pub total: usize,
+    pub converted: usize,
+    pub skipped: Vec<SectionError>,
+    pub defaulted: Vec<SectionError>,
+    /// Meetings left out of class schedules by exclusion rules
+    #[serde(default)]
+    pub excluded: usize,
+}
+
+impl Display for ConversionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Converted {} of {} sections ({} skipped, {} fields defaulted)",
+            self.converted,
+            self.total,
+            self.skipped.len(),
+            self.defaulted.len()
+        )?;
+
+        if self.excluded > 0 {
+            write!(f, "\nExcluded {} meetings", self.excluded)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Conversion {
+    /// The converted classes, or an error listing every section that failed
+    pub fn into_bank(self) -> anyhow::Result<ClassBank> {
+        self.check(0)?;
+
+        Ok(self.bank)
+    }
+
+    /// Fails when more than `max_skipped` sections had to be left out
+    pub fn check(&self, max_skipped: usize) -> anyhow::Result<()> {
+        if self.errors.len() > max_skipped {
+            let mut message = format!(
+                "{} of {} sections could not be converted (at most {} allowed)",
+                self.errors.len(),
+                self.total,
+                max_skipped
+            );
+            for error in &self.errors {
+                message.push_str("\n\t");
+                message.push_str(&error.to_string());
+            }
+
+            bail!(message);
+        }
+
+        Ok(())
+    }
+
+    pub fn report(&self, strictness: Strictness) -> ConversionReport {
+        ConversionReport {
+            strictness,
+            total: self.total,
+            converted: self.bank.len(),
+            skipped: self.errors.clone(),
+            defaulted: self.defaulted.clone(),
+            excluded: 0,
+        }
+    }
+}
+
+#[derive(Args, Clone, Debug, Default)]
+pub struct ConvertArgs {
+    /// Keep sections with missing or malformed fields by defaulting everything but the CRN and subject
+    #[clap(long)]
+    pub lenient: bool,
+    /// How many sections may be skipped before failing, defaults to none when strict and any when lenient
+    #[clap(long)]
+    pub max_skipped: Option<usize>,
+    /// Where to write a JSON report of skipped and defaulted sections
+    #[clap(long)]
+    pub report: Option<PathBuf>,
+    /// Leave matching meetings out of class schedules: `type:<name>`, `dates:<start>..<end>` or
+    /// `time:<HHMM>-<HHMM>[@<days>]` (ex: `type:Exam`, `time:1910-2030@MW`), can be repeated
+    #[clap(long = "exclude", value_name = "RULE")]
+    pub exclusions: Vec<Exclusion>,
+}
+
+impl ConvertArgs {
+    pub fn strictness(&self) -> Strictness {
+        if self.lenient {
+            Strictness::Lenient
+        } else {
+            Strictness::Strict
+        }
+    }
+
+    /// Converts the sections, writes the report if one was asked for, and enforces `max_skipped`
+    pub fn convert(&self, sections: &[Value]) -> anyhow::Result<(ClassBank, ConversionReport)> {
+        let strictness = self.strictness();
+        let mut conversion = convert(sections, strictness);
+        let excluded = match self.exclusions.is_empty() {
+            true => 0,
+            false => exclusion::apply(&mut conversion.bank, &self.exclusions),
+        };
+        let report = ConversionReport {
+            excluded,
+            ..conversion.report(strictness)
+        };
+
+        if let Some(path) = &self.report {
+            let json =
+                serde_json::to_string_pretty(&report).context("Could not serialize report")?;
+            fs::write(path, json).with_context(|| format!("Could not write {}", path.display()))?;
+        }
+
+        let max_skipped = self.max_skipped.unwrap_or(match strictness {
+            Strictness::Strict => 0,
+            Strictness::Lenient => usize::MAX,
+        });
+        conversion.check(max_skipped)?;
+
+        Ok((conversion.bank, report))
+    }
+}
+
+/// Pulls the section list out of a raw Banner response (or a `raw_data.json` written by download)
+pub fn sections(raw: &Value) -> anyhow::Result<&[Value]> {
+    raw.get("data")
+        .and_then(|data| data.as_array())
+        .map(|data| data.as_slice())
+        .context("Missing `data` array")
+}
+
+/// Converts every section it can, sections that fail are reported instead of aborting the rest
+pub fn convert(sections: &[Value], strictness: Strictness) -> Conversion {
+    let mut conversion = Conversion {
+        total: sections.len(),
+        ..Default::default()
+    };
+
+    for (index, raw) in sections.iter().enumerate() {
+        match convert_section(index, raw, strictness) {
+            Ok((class, defaulted)) => {
+                conversion.bank.insert(class.crn, class);
+                conversion.defaulted.extend(defaulted);
+            }
+            Err(error) => conversion.errors.push(error),
+        }
+    }
+
+    conversion
+}
+
+/// Converts a single section, also returning the fields that had to be defaulted
+pub fn convert_section(
+    index: usize,
+    raw: &Value,
+    strictness: Strictness,
+) -> Result<(Class, Vec<SectionError>), SectionError> {
+    let crn = raw
+        .get("courseReferenceNumber")
+        .and_then(|val| val.as_str())
+        .map(|val| val.to_owned());
+    let section_error = |FieldError { field, reason }| SectionError {
+        index,
+        crn: crn.clone(),
+        field,
+        reason,
+    };
+
+    let mut fields = Fields {
+        strictness,
+        defaulted: Vec::new(),
+    };
+
+    let mut raw = Cow::Borrowed(raw);
+    let section: BannerSection = loop {
+        match serde_path_to_error::deserialize(&*raw) {
+            Ok(section) => break section,
+            Err(error) => {
+                let field = error.path().to_string();
+                let reason = error.into_inner().to_string();
+
+                // Drop malformed values and try again, the field is then treated as missing
+                if strictness == Strictness::Lenient && field != "." && remove(raw.to_mut(), &field)
+                {
+                    fields.defaulted.push(FieldError {
+                        field,
+                        reason: format!("{reason}, removed"),
+                    });
+                } else {
+                    return Err(section_error(FieldError { field, reason }));
+                }
+            }
+        }
+    };
+
+    let class = section.into_class(&mut fields).map_err(section_error)?;
+    let defaulted = fields.defaulted.into_iter().map(section_error).collect();
+
+    Ok((class, defaulted))
+}
+
+// Removes the value at a path formatted by `serde_path_to_error` (ex: `faculty[0].displayName`)
+fn remove(value: &mut Value, path: &str) -> bool {
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, indices) = part.split_once('[').unwrap_or((part, ""));
+        if !key.is_empty() {
+            segments.push(Ok(key));
+        }
+        for index in indices.split('[') {
+            if let Some(index) = index.strip_suffix(']').and_then(|it| it.parse().ok()) {
+                segments.push(Err(index));
+            }
+        }
+    }
+
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+
+    let mut current = value;
+    for segment in parents {
+        let next = match segment {
+            Ok(key) => current.get_mut(*key),
+            Err(index) => current.get_mut(*index),
+        };
+
+        match next {
+            Some(next) => current = next,
+            None => return false,
+        }
+    }
+
+    match (last, current) {
+        (Ok(key), Value::Object(map)) => map.remove(*key).is_some(),
+        (Err(index), Value::Array(array)) if *index < array.len() => {
+            array.remove(*index);
+            true
+        }
+        _ => false,
+    }
+}
+
+#[derive(Clone, Debug)]
+struct FieldError {
+    field: String,
+    reason: String,
+}
+
+struct Fields {
+    strictness: Strictness,
+    defaulted: Vec<FieldError>,
+}
+
+impl Fields {
+    // Fields a class is useless without, missing ones always skip the section
+    fn required<T>(&self, value: Option<T>, field: impl Into<String>) -> Result<T, FieldError> {
+        value.ok_or_else(|| FieldError {
+            field: field.into(),
+            reason: "missing".to_owned(),
+        })
+    }
+
+    // Fields that get defaulted in lenient mode
+    fn or_default<T: Default + std::fmt::Debug>(
+        &mut self,
+        value: Option<T>,
+        field: impl Into<String>,
+    ) -> Result<T, FieldError> {
+        match value {
+            Some(value) => Ok(value),
+            None if self.strictness == Strictness::Lenient => {
+                let default = T::default();
+                self.defaulted.push(FieldError {
+                    field: field.into(),
+                    reason: format!("missing, defaulted to {default:?}"),
+                });
+
+                Ok(default)
+            }
+            None => self.required(None, field),
+        }
+    }
+
+    // Unknown dates are left open in lenient mode
+    fn date(
+        &mut self,
+        value: Option<String>,
+        field: impl Into<String>,
+    ) -> Result<Option<NaiveDate>, FieldError> {
+        let field = field.into();
+
+        match self.required(value, &field).and_then(|date| {
+            parse_date(&date).map_err(|error| FieldError {
+                field: field.clone(),
+                reason: error.to_string(),
+            })
+        }) {
+            Ok(date) => Ok(Some(date)),
+            Err(error) if self.strictness == Strictness::Lenient => {
+                self.defaulted.push(FieldError {
+                    field,
+                    reason: format!("{}, left open", error.reason),
+                });
+
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    // Sections without set times send no times at all, ones that can't be read are left open in
+    // lenient mode
+    fn time(
+        &mut self,
+        value: Option<String>,
+        field: impl Into<String>,
+    ) -> Result<Option<Time>, FieldError> {
+        let Some(value) = value else {
+            return Ok(None);
+        };
+
+        match value.parse() {
+            Ok(time) => Ok(Some(time)),
+            Err(_) => {
+                let error = FieldError {
+                    field: field.into(),
+                    reason: format!("Bad time: {value}"),
+                };
+                if self.strictness == Strictness::Strict {
+                    return Err(error);
+                }
+
+                self.defaulted.push(FieldError {
+                    reason: format!("{}, left open", error.reason),
+                    ..error
+                });
+
+                Ok(None)
+            }
+        }
+    }
+
+    // Parts of a section that get left out in lenient mode
+    fn drop(&mut self, field: impl Into<String>, what: &str) -> Result<(), FieldError> {
+        match self.strictness {
+            Strictness::Lenient => {
+                self.defaulted.push(FieldError {
+                    field: field.into(),
+                    reason: format!("missing, {what} dropped"),
+                });
+
+                Ok(())
+            }
+            Strictness::Strict => self.required(None, field),
+        }
+    }
+}
+
+impl BannerSection {
+    fn into_class(self, fields: &mut Fields) -> Result<Class, FieldError> {
+        let crn = fields.required(self.course_reference_number, "courseReferenceNumber")?;
+        let crn = crn.parse::<Crn>().map_err(|_| FieldError {
+            field: "courseReferenceNumber".to_owned(),
+            reason: format!("`{crn}` is not a number"),
+        })?;
+
+        let credit_hours = CreditHours {
+            credit_hour_high: self.credit_hour_high,
+            credit_hour_low: self.credit_hour_low,
+            credit_hours: self.credit_hours,
+        };
+
+        let cross_list = match self.cross_list.as_ref().and_then(|val| val.as_u64()) {
+            Some(cross_list) => Some(CrossList {
+                cross_list,
+                cross_list_available: fields
+                    .or_default(self.cross_list_available, "crossListAvailable")?,
+                cross_list_capacity: fields
+                    .or_default(self.cross_list_capacity, "crossListCapacity")?,
+                cross_list_count: fields.or_default(self.cross_list_count, "crossListCount")?,
+            }),
+            None => None,
+        };
+
+        let enrollment = Enrollment {
+            count: fields.or_default(self.enrollment, "enrollment")?,
+            capacity: fields.or_default(self.maximum_enrollment, "maximumEnrollment")?,
+            available: fields.or_default(self.seats_available, "seatsAvailable")?,
+        };
+
+        let wait_list = Enrollment {
+            count: fields.or_default(self.wait_count, "waitCount")?,
+            capacity: fields.or_default(self.wait_capacity, "waitCapacity")?,
+            available: fields.or_default(self.wait_available, "waitAvailable")?,
+        };
+
+        let mut faculty = Vec::new();
+        let banner_faculty = fields.or_default(self.faculty, "faculty")?;
+        for (idx, faculty1) in banner_faculty.into_iter().enumerate() {
+            faculty.push(Faculty {
+                name: fields
+                    .or_default(faculty1.display_name, format!("faculty[{idx}].displayName"))?,
+                email: faculty1.email_address,
+                primary: fields.or_default(
+                    faculty1.primary_indicator,
+                    format!("faculty[{idx}].primaryIndicator"),
+                )?,
+            })
+        }
+
+        let mut meetings = Vec::new();
+        let meetings_faculty = fields.or_default(self.meetings_faculty, "meetingsFaculty")?;
+        for (idx, session) in meetings_faculty.into_iter().enumerate() {
+            let field = |name: &str| format!("meetingsFaculty[{idx}].meetingTime.{name}");
+            let Some(session) = session.meeting_time else {
+                // A meeting without a time has nothing worth keeping
+                fields.drop(format!("meetingsFaculty[{idx}].meetingTime"), "meeting")?;
+                continue;
+            };
+
+            meetings.push(Session {
+                start_time: fields.time(session.begin_time, field("beginTime"))?,
+                end_time: fields.time(session.end_time, field("endTime"))?,
+                start_date: fields.date(session.start_date, field("startDate"))?,
+                end_date: fields.date(session.end_date, field("endDate"))?,
+                days: Days {
+                    monday: fields.or_default(session.monday, field("monday"))?,
+                    tuesday: fields.or_default(session.tuesday, field("tuesday"))?,
+                    wednesday: fields.or_default(session.wednesday, field("wednesday"))?,
+                    thursday: fields.or_default(session.thursday, field("thursday"))?,
+                    friday: fields.or_default(session.friday, field("friday"))?,
+                    saturday: fields.or_default(session.saturday, field("saturday"))?,
+                    sunday: fields.or_default(session.sunday, field("sunday"))?,
+                },
+                building_code: session.building,
+                building_name: session.building_description,
+                room: session.room.and_then(|val| val.parse::<u64>().ok()),
+                meeting_type: fields.or_default(
+                    session.meeting_type_description,
+                    field("meetingTypeDescription"),
+                )?,
+                excluded: false,
+            })
+        }
+
+        let campus = fields.or_default(self.campus_description, "campusDescription")?;
+        let schedule = Schedule::from_meetings(&campus, &meetings);
+
+        Ok(Class {
+            campus,
+            crn,
+            course_number: fields.or_default(self.course_number, "courseNumber")?,
+            name: fields.or_default(self.course_title, "courseTitle")?,
+            credit_hours,
+            cross_list,
+            enrollment,
+            wait_list,
+            faculty,
+            instructional_method: fields.or_default(
+                self.instructional_method_description,
+                "instructionalMethodDescription",
+            )?,
+            meetings,
+            open: fields.or_default(self.open_section, "openSection")?,
+            part_of_term: fields
+                .or_default(self.part_of_term_description, "partOfTermDescription")?,
+            schedule_type: fields
+                .or_default(self.schedule_type_description, "scheduleTypeDescription")?,
+            sequence_number: fields.or_default(self.sequence_number, "sequenceNumber")?,
+            special_approval: self.special_approval_description,
+            subject_course: fields.required(self.subject_course, "subjectCourse")?,
+            subject_description: fields
+                .or_default(self.subject_description, "subjectDescription")?,
+            term: fields.or_default(self.term_desc, "termDesc")?,
+            schedule,
+        })
+    }
+}
@@ -1,98 +1,107 @@
+use anyhow::Context;
 use clap::Parser;
-use reqwest::{Client, StatusCode};
-use serde::Serialize;
-use serde_json::Value;
+use reqwest::Client;
+use schedual::banner::ConvertArgs;
+use schedual::download::{self, DownloadOptions};
+use schedual::term::{self, TermArgs, TermFolder};
+use std::path::PathBuf;
 use std::time::Duration;
-use tokio::fs::OpenOptions;
-use tokio::io::AsyncWriteExt;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> anyhow::Result<()> {
     let args: Args = Args::parse();
 
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create_new(true)
-        .open("./fall2023/raw_data.json")
-        .await
-        .expect("open");
+    let term_folder = || -> anyhow::Result<TermFolder> {
+        let folder = args.term_folder.resolve()?;
+        folder.create()?;
+        Ok(folder)
+    };
+
+    let output = match &args.output {
+        Some(output) => output.clone(),
+        None => term_folder()?.raw_data(),
+    };
 
     println!("Using endpoint: {}", args.endpoint);
-    println!("Using term: {}", args.term);
+    println!("Using term: {}", args.banner_term);
     println!("Using cookies: {}", args.cookies);
+    println!("Using output: {}", output.display());
+
+    let options = DownloadOptions {
+        page_size: args.page_size,
+        delay: args.delay,
+        retries: args.retries,
+        backoff: args.backoff,
+        ..DownloadOptions::new(
+            args.endpoint.clone(),
+            args.banner_term.clone(),
+            args.cookies.clone(),
+        )
+    };
 
-    let mut data = Vec::new();
-
-    let client = Client::new();
-    loop {
-        let response = client
-            .get(&args.endpoint)
-            .header("Cookie", &args.cookies)
-            .query(&[("txt_term", &args.term)])
-            .query(&[("pageOffset", &data.len().to_string())])
-            .query(&[("pageMaxSize", "1000")])
-            .query(&[("sortColumn", "subjectDescription")])
-            .query(&[("sortDirection", "asc")])
-            .send()
-            .await
-            .expect("get");
-        assert_eq!(response.status(), StatusCode::OK);
-        //println!("{:?}", response);
-
-        let json: Value = response.json().await.expect("json");
-        // println!("{:?}", json);
-
-        let success = json
-            .get("success")
-            .and_then(|success| success.as_bool())
-            .unwrap_or_default();
-        assert!(success);
-
-        let new_data = json
-            .get("data")
-            .and_then(|data| data.as_array())
-            .expect("class data");
-
-        if new_data.len() == 0 {
-            println!("Hit 0 len");
-            break;
-        }
-
-        println!("Pulled {}", new_data.len());
-
-        data.extend_from_slice(&new_data);
-
-        let sections_fetched_count = json
-            .get("sectionsFetchedCount")
-            .and_then(|sections| sections.as_u64())
-            .expect("Sections");
-
-        if data.len() >= sections_fetched_count as usize {
-            break;
-        }
-
-        tokio::time::sleep(Duration::from_secs(7)).await;
+    let sections = download::download(&Client::new(), &options, &output, &mut |progress| {
+        println!("{progress}")
+    })
+    .await?;
+    println!("Downloaded {} sections", sections.len());
+
+    if !args.raw_only {
+        let bank_output = match &args.bank_output {
+            Some(bank_output) => bank_output.clone(),
+            None => term_folder()?.data(),
+        };
+
+        let (classes, report) = args.convert.convert(&sections)?;
+        println!("{report}");
+        term::save_bank(&bank_output, &classes)?;
+
+        println!(
+            "Wrote {} classes to {}",
+            classes.len(),
+            bank_output.display()
+        );
     }
 
-    let structure = BasicStructure {
-        data: Value::Array(data),
-    };
-
-    let string = serde_json::to_string_pretty(&structure).expect("to string");
-    file.write_all(string.as_bytes()).await.expect("write");
+    Ok(())
 }
 
 #[derive(Parser, Clone, Debug)]
 struct Args {
     #[clap(short = 'e')]
     endpoint: String,
+    /// Banner term code (ex: 202380)
     #[clap(short = 't')]
-    term: String,
+    banner_term: String,
     #[clap(short = 'c')]
     cookies: String,
+    /// Where to write the raw Banner data, defaults to `raw_data.json` in the term folder
+    #[clap(short = 'o', long)]
+    output: Option<PathBuf>,
+    /// Where to write the converted class bank, defaults to `data.json` in the term folder
+    #[clap(long)]
+    bank_output: Option<PathBuf>,
+    /// Only save the raw Banner data, run `convert` on it later
+    #[clap(long)]
+    raw_only: bool,
+    /// Sections requested per page
+    #[clap(short = 'p', long, default_value_t = 1000)]
+    page_size: usize,
+    /// Seconds to wait between pages
+    #[clap(short = 'd', long, default_value = "7", value_parser = seconds)]
+    delay: Duration,
+    /// How many times a failed page is retried before giving up
+    #[clap(short = 'r', long, default_value_t = 5)]
+    retries: u32,
+    /// Seconds to wait before the first retry, doubled after every failed retry
+    #[clap(short = 'b', long, default_value = "10", value_parser = seconds)]
+    backoff: Duration,
+    #[clap(flatten)]
+    convert: ConvertArgs,
+    #[clap(flatten)]
+    term_folder: TermArgs,
 }
 
-#[derive(Serialize)]
-struct BasicStructure {
-    data: Value,
+fn seconds(arg: &str) -> anyhow::Result<Duration> {
+    let seconds = arg.parse::<f64>()?;
+    Duration::try_from_secs_f64(seconds).context("Must be a finite number of seconds, at least 0")
 }
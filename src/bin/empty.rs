@@ -1,5 +1,7 @@
 use chrono::{Datelike, Local, Timelike, Weekday};
+use clap::Parser;
 use fxhash::FxHashMap as HashMap;
+use schedual::term::TermArgs;
 use schedual::{ClassBank, Day, Days, Time};
 use std::collections::BTreeMap;
 use std::ops::{RangeInclusive, Sub};
@@ -8,20 +10,19 @@ type Room = (String, String, u64); // Building & Room
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let args: Args = Args::parse();
+
     let mut default_day_map: BTreeMap<Day, TimeSeries> = BTreeMap::new();
     for day in Days::everyday().iter() {
         default_day_map.insert(day, TimeSeries::new(Time::new(0, 00), Time::new(24, 00)));
     }
     let default_day_map = default_day_map;
 
-    let data = tokio::fs::read_to_string("spring2023/data.json")
-        .await
-        .unwrap();
-    let classes: ClassBank = serde_json::from_str(&data)?;
+    let classes: ClassBank = args.term_folder.resolve()?.load_bank()?;
 
     let mut data: HashMap<Room, BTreeMap<Day, TimeSeries>> = HashMap::default();
 
-    for (_, class) in &classes {
+    for class in classes.values() {
         for meeting in &class.meetings {
             if let (Some(start), Some(end), Some(building), Some(room)) = (
                 meeting.start_time,
@@ -88,7 +89,7 @@ async fn main() -> anyhow::Result<()> {
                         - (time.hour as u64 * 60 + time.min as u64);
                     free_rooms
                         .entry(room.0.clone())
-                        .or_insert_with(|| Vec::new())
+                        .or_insert_with(Vec::new)
                         .push((room.clone(), min));
                 }
             }
@@ -115,6 +116,12 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[derive(Parser, Clone, Debug)]
+struct Args {
+    #[clap(flatten)]
+    term_folder: TermArgs,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct TimeSeries {
     ranges: Vec<RangeInclusive<Time>>, // Non overlapping ranges from least to greatest
@@ -1,13 +1,14 @@
+use clap::Parser;
 use fxhash::FxHashMap as HashMap;
 use itertools::Itertools;
+use schedual::term::TermArgs;
 use schedual::ClassBank;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let data = tokio::fs::read_to_string("spring2023/data.json")
-        .await
-        .unwrap();
-    let classes: ClassBank = serde_json::from_str(&data)?;
+    let args: Args = Args::parse();
+
+    let classes: ClassBank = args.term_folder.resolve()?.load_bank()?;
     let mut counters = HashMap::default();
 
     'mainloop: for (_, class) in classes {
@@ -29,3 +30,9 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[derive(Parser, Clone, Debug)]
+struct Args {
+    #[clap(flatten)]
+    term_folder: TermArgs,
+}
@@ -1,10 +1,11 @@
-use std::fs;
-
+use clap::Parser;
+use schedual::term::TermArgs;
 use schedual::ClassBank;
 
 fn main() -> anyhow::Result<()> {
-    let data = fs::read_to_string("spring2023bak/data.json").unwrap();
-    let classes: ClassBank = serde_json::from_str(&data)?;
+    let args: Args = Args::parse();
+
+    let classes: ClassBank = args.term_folder.resolve()?.load_bank()?;
 
     let mut bad_start = 0;
     let mut bad_end = 0;
@@ -52,3 +53,9 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[derive(Parser, Clone, Debug)]
+struct Args {
+    #[clap(flatten)]
+    term_folder: TermArgs,
+}
@@ -1,41 +1,78 @@
+use chrono::Utc;
+use clap::Parser;
 use eframe::{Frame, Storage};
 use egui::{
     Align2, Color32, ComboBox, Context, FontId, Id, Painter, Rect, RichText, Rounding, Sense,
     Stroke, Ui, Vec2,
 };
-use schedual::solver::{Constraint, Include, Priorities, ScheduleOwned};
-use schedual::{solver, ClassBank, Crn, Day, Days, Time};
-use serde::{Deserialize, Serialize};
-use std::collections::hash_map::DefaultHasher;
-use std::collections::{BTreeSet, HashMap};
+use itertools::Itertools;
+use schedual::link::{Link, LinkRule};
+use schedual::plan::Plan;
+use schedual::solver::{Constraint, Include, PreferredInstructor, Priorities, ScheduleOwned};
+use schedual::term::TermArgs;
+use schedual::timetable::{self, Timetable};
+use schedual::travel::TravelTime;
+use schedual::{diagnosis, exclusion, html, ics, solver, ClassBank, Crn, Days, Delivery, Time};
+use std::collections::BTreeSet;
 use std::fs;
-use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::thread;
 use std::time::{Duration, Instant};
 
 // TODO use crate `graphql_client` and the endpoint https://www.ratemyprofessors.com/graphql to get teacher info
 // TODO better input validation
 
 fn main() -> anyhow::Result<()> {
-    let data = fs::read_to_string("spring2023/data.json").unwrap();
-    let classes: ClassBank = serde_json::from_str(&data)?;
+    let args: Args = Args::parse();
+
+    let classes: ClassBank = args.term_folder.resolve()?.load_bank()?;
+    let plan = match &args.plan {
+        Some(path) => Some((path.clone(), Plan::load(path)?)),
+        None => None,
+    };
 
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(
         "Scheduler",
         native_options,
-        Box::new(|cc| Box::new(ScheduleApp::new(classes, cc))),
+        Box::new(|cc| Box::new(ScheduleApp::new(classes, plan, cc))),
     );
 
     Ok(())
 }
 
+#[derive(Parser, Clone, Debug)]
+struct Args {
+    /// Plan file (TOML or JSON) to start with instead of the last session
+    #[clap(long)]
+    plan: Option<PathBuf>,
+    #[clap(flatten)]
+    term_folder: TermArgs,
+}
+
 #[derive(Default)]
 struct ScheduleApp {
     raw_classes: ClassBank,
     persistent: PersistentData,
+    plan_path: String,
+    plan_status: Option<Result<String, String>>,
+    ics_path: String,
+    html_path: String,
+    export_status: Option<Result<String, String>>,
+    exclusion_rule: String,
+    exclusion_error: Option<String>,
+    // Lead course, follow course and sequence prefix length of the link being added
+    new_link: (String, String, usize),
+    // Course and name of the preferred instructor being added
+    new_instructor: (String, String),
+    // From, to and minutes of the travel time being added
+    new_travel: (String, String, u16),
+    // Course and text of the filter being added
+    new_filter: (String, String),
+    filter_error: Option<String>,
+    diagnosis: Option<String>,
 
     search_time: Duration,
-    total_solutions: usize,
     sorted_schedules: Vec<((f64, Priorities), ScheduleOwned)>,
 
     create_class_window: Option<CreateClassWindow>,
@@ -43,27 +80,30 @@ struct ScheduleApp {
     displayed_schedules: Vec<DisplayedSchedule>,
 }
 
-#[derive(Default, Serialize, Deserialize)]
-#[serde(default)]
-struct PersistentData {
-    constraints: Vec<Constraint>,
-    includes: Vec<Include>,
-    priorities: Priorities,
-    // todo filters?
-}
+// Stored in the same shape as plan files so they can be loaded and saved as is
+type PersistentData = Plan;
 
 // TODO better way than String?
-struct CreateClassWindow(Include, String);
+struct CreateClassWindow(Include, String, bool);
 struct CreateConstraintWindow(Constraint, String, String);
 struct DisplayedSchedule(((f64, Priorities), ScheduleOwned));
 
 impl ScheduleApp {
-    fn new(raw_classes: ClassBank, cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(
+        raw_classes: ClassBank,
+        plan: Option<(PathBuf, Plan)>,
+        cc: &eframe::CreationContext<'_>,
+    ) -> Self {
         // This is also where you can customized the look at feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
 
-        let persistent = if let Some(storage) = cc.storage {
-            eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
+        let (plan_path, persistent) = if let Some((path, plan)) = plan {
+            (path.display().to_string(), plan)
+        } else if let Some(storage) = cc.storage {
+            (
+                String::new(),
+                eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default(),
+            )
         } else {
             Default::default()
         };
@@ -71,47 +111,60 @@ impl ScheduleApp {
         Self {
             raw_classes,
             persistent,
+            plan_path,
+            ics_path: "schedule.ics".to_owned(),
+            html_path: "schedule.html".to_owned(),
+            new_link: (String::new(), String::new(), 2),
             ..Default::default()
         }
     }
 
-    // TODO run in parallel
+    fn load_plan(&mut self) {
+        self.plan_status = Some(match Plan::load(&self.plan_path) {
+            Ok(plan) => {
+                self.persistent = plan;
+                Ok(format!("Loaded {}", self.plan_path))
+            }
+            Err(error) => Err(format!("{:#}", error)),
+        });
+    }
+
+    fn save_plan(&mut self) {
+        self.plan_status = Some(match self.persistent.save(&self.plan_path) {
+            Ok(()) => Ok(format!("Saved {}", self.plan_path)),
+            Err(error) => Err(format!("{:#}", error)),
+        });
+    }
+
     fn generate_schedules(&mut self, take: usize) {
         let start = Instant::now();
 
-        let classes = &self.raw_classes;
-        let includes = &self.persistent.includes;
-        let constraints = &self.persistent.constraints;
-        let priorities = &self.persistent.priorities;
-
-        // Filter classes
-        let classes = solver::include_classes(classes, includes, HashMap::new());
-        let classes = solver::filter_classes(classes, constraints);
-        let classes = solver::validate_classes(classes);
+        let mut bank = self.raw_classes.clone();
+        exclusion::apply(&mut bank, &self.persistent.exclusions);
 
-        // Bruteforce schedules
-        let schedules = solver::bruteforce_schedules(classes, Vec::new())
+        // Search for the best schedules
+        let threads = thread::available_parallelism()
+            .map(|it| it.get())
+            .unwrap_or(1);
+        self.sorted_schedules = self
+            .persistent
+            .solve(&bank, take, threads)
+            .schedules
             .into_iter()
-            .collect::<Vec<_>>();
-        self.total_solutions = schedules.len();
-
-        // Score schedules
-        // todo is a btree map faster
-        let mut scored_schedules = Vec::new();
-        for schedule in schedules {
-            scored_schedules.push((priorities.score(&schedule), schedule));
-        }
-        scored_schedules.sort_by(|((a, _), _), ((b, _), _)| f64::total_cmp(a, b).reverse());
-
-        self.sorted_schedules = scored_schedules
-            .into_iter()
-            .take(take)
-            .map(|((score, breakdown), schedule)| {
-                let owned = schedule.into_iter().cloned().collect();
-                ((score, breakdown), owned)
+            .map(|(score, soloution)| {
+                let owned = solver::unmap_classes(&bank, &soloution)
+                    .into_iter()
+                    .cloned()
+                    .collect();
+                (score, owned)
             })
             .collect();
 
+        self.diagnosis = self
+            .sorted_schedules
+            .is_empty()
+            .then(|| diagnosis::diagnose(&bank, &self.persistent).to_string());
+
         self.search_time = start.elapsed();
     }
 }
@@ -120,6 +173,28 @@ impl eframe::App for ScheduleApp {
     fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Schedule solver");
+            ui.collapsing("Plan file", |ui| {
+                ui.label("Path: ");
+                ui.text_edit_singleline(&mut self.plan_path);
+                ui.horizontal(|ui| {
+                    if ui.button("Load").clicked() {
+                        self.load_plan();
+                    }
+                    if ui.button("Save").clicked() {
+                        self.save_plan();
+                    }
+                });
+
+                match &self.plan_status {
+                    Some(Ok(status)) => {
+                        ui.label(status);
+                    }
+                    Some(Err(error)) => {
+                        ui.label(RichText::new(error).color(Color32::RED));
+                    }
+                    None => {}
+                }
+            });
             ui.collapsing("Classes", |ui| {
                 if ui.button("Add Class").clicked() {
                     self.create_class_window = Some(CreateClassWindow(
@@ -128,6 +203,7 @@ impl eframe::App for ScheduleApp {
                             course_type: None,
                         },
                         String::new(),
+                        false,
                     ));
                 }
 
@@ -152,6 +228,12 @@ impl eframe::App for ScheduleApp {
                                 course_type.as_ref().map(|it| it.as_str()).unwrap_or("")
                             ));
                         }
+                        Include::All => {
+                            ui.label("All classes");
+                        }
+                        Include::AnyOf { .. } | Include::Optional { .. } => {
+                            ui.label(include.to_string());
+                        }
                     }
 
                     if ui.button("Remove").clicked() {
@@ -176,8 +258,7 @@ impl eframe::App for ScheduleApp {
 
                 let mut remove = None;
                 for (idx, include) in self.persistent.constraints.iter().enumerate() {
-                    // TODO improve
-                    ui.label(format!("{:?}", include));
+                    ui.label(include.to_string());
 
                     if ui.button("Remove").clicked() {
                         remove = Some(idx);
@@ -188,6 +269,106 @@ impl eframe::App for ScheduleApp {
                     self.persistent.constraints.remove(idx);
                 }
             });
+            ui.collapsing("Exclusions", |ui| {
+                ui.label("Rule (type:Exam, dates:2023-10-16..2023-12-08, time:1910-2030@MW): ");
+                ui.text_edit_singleline(&mut self.exclusion_rule);
+                if ui.button("Add Exclusion").clicked() {
+                    match self.exclusion_rule.parse() {
+                        Ok(exclusion) => {
+                            self.persistent.exclusions.push(exclusion);
+                            self.exclusion_rule.clear();
+                            self.exclusion_error = None;
+                        }
+                        Err(error) => self.exclusion_error = Some(format!("{:#}", error)),
+                    }
+                }
+                if let Some(error) = &self.exclusion_error {
+                    ui.label(RichText::new(error).color(Color32::RED));
+                }
+
+                let mut remove = None;
+                for (idx, exclusion) in self.persistent.exclusions.iter().enumerate() {
+                    ui.label(exclusion.to_string());
+
+                    if ui.button("Remove").clicked() {
+                        remove = Some(idx);
+                    }
+                }
+
+                if let Some(idx) = remove {
+                    self.persistent.exclusions.remove(idx);
+                }
+            });
+            ui.collapsing("Filters", |ui| {
+                let (course, rule) = &mut self.new_filter;
+                ui.label("Course (ex: PHY2048): ");
+                ui.text_edit_singleline(course);
+                ui.label(
+                    "Filter (ex: campus = \"Boca Raton\" and not instructional_method ~ online): ",
+                );
+                ui.text_edit_singleline(rule);
+                if ui.button("Add Filter").clicked() {
+                    match rule.parse() {
+                        Ok(filter) => {
+                            self.persistent
+                                .filters
+                                .insert(course.trim().to_uppercase(), filter);
+                            rule.clear();
+                            self.filter_error = None;
+                        }
+                        Err(error) => self.filter_error = Some(format!("{:#}", error)),
+                    }
+                }
+                if let Some(error) = &self.filter_error {
+                    ui.label(RichText::new(error).color(Color32::RED));
+                }
+
+                let mut remove = None;
+                for (course, filter) in &self.persistent.filters {
+                    ui.label(format!("{course}: {filter}"));
+
+                    if ui.button("Remove").clicked() {
+                        remove = Some(course.clone());
+                    }
+                }
+
+                if let Some(course) = remove {
+                    self.persistent.filters.remove(&course);
+                }
+            });
+            ui.collapsing("Linked sections", |ui| {
+                let (lead, follow, len) = &mut self.new_link;
+                ui.label("Lead course (ex: PHY2048): ");
+                ui.text_edit_singleline(lead);
+                ui.label("Follow course (ex: PHY2048L): ");
+                ui.text_edit_singleline(follow);
+                ui.label("Matching sequence number characters");
+                ui.add(egui::Slider::new(len, 1..=4));
+                if ui.button("Add Link").clicked() {
+                    let course = |subject: &str| Include::Course {
+                        subject: subject.trim().to_uppercase(),
+                        course_type: None,
+                    };
+                    self.persistent.links.push(Link {
+                        lead: course(lead),
+                        follow: course(follow),
+                        rule: LinkRule::SequencePrefix { len: *len },
+                    });
+                }
+
+                let mut remove = None;
+                for (idx, link) in self.persistent.links.iter().enumerate() {
+                    ui.label(link.to_string());
+
+                    if ui.button("Remove").clicked() {
+                        remove = Some(idx);
+                    }
+                }
+
+                if let Some(idx) = remove {
+                    self.persistent.links.remove(idx);
+                }
+            });
             ui.collapsing("Priorities", |ui| {
                 let priorities = &mut self.persistent.priorities;
                 ui.label("Time between classes");
@@ -197,12 +378,12 @@ impl eframe::App for ScheduleApp {
                 ));
                 ui.label("Similar Start Times");
                 ui.add(egui::Slider::new(
-                    &mut priorities.start_time,
+                    &mut priorities.similar_start_time,
                     -5.0..=5.0,
                 ));
                 ui.label("Similar End Times");
                 ui.add(egui::Slider::new(
-                    &mut priorities.end_time,
+                    &mut priorities.similar_end_time,
                     -5.0..=5.0,
                 ));
                 ui.label("Free Time Blocks");
@@ -211,16 +392,102 @@ impl eframe::App for ScheduleApp {
                 ui.add(egui::Slider::new(&mut priorities.free_day, -5.0..=5.0));
                 ui.label("Day Length");
                 ui.add(egui::Slider::new(&mut priorities.day_length, -5.0..=5.0));
+                ui.label("Optional Courses");
+                ui.add(egui::Slider::new(
+                    &mut priorities.optional_course,
+                    -5.0..=50.0,
+                ));
+                ui.label("Free Seats");
+                ui.add(egui::Slider::new(&mut priorities.free_seats, -5.0..=5.0));
+                ui.label("Preferred Instructors");
+                ui.add(egui::Slider::new(
+                    &mut priorities.preferred_instructor,
+                    -50.0..=50.0,
+                ));
+
+                let (course, name) = &mut self.new_instructor;
+                ui.label("Course (empty for every course): ");
+                ui.text_edit_singleline(course);
+                ui.label("Instructor: ");
+                ui.text_edit_singleline(name);
+                if ui.button("Add Instructor").clicked() && !name.trim().is_empty() {
+                    self.persistent.instructors.push(PreferredInstructor {
+                        course: Some(course.trim().to_uppercase()).filter(|it| !it.is_empty()),
+                        name: name.trim().to_owned(),
+                    });
+                    name.clear();
+                }
+
+                let mut remove = None;
+                for (idx, instructor) in self.persistent.instructors.iter().enumerate() {
+                    match &instructor.course {
+                        Some(course) => ui.label(format!("{} for {}", instructor.name, course)),
+                        None => ui.label(&instructor.name),
+                    };
+
+                    if ui.button("Remove").clicked() {
+                        remove = Some(idx);
+                    }
+                }
+
+                if let Some(idx) = remove {
+                    self.persistent.instructors.remove(idx);
+                }
+            });
+            ui.collapsing("Travel", |ui| {
+                ui.label("Tight Transfers");
+                ui.add(egui::Slider::new(
+                    &mut self.persistent.priorities.tight_transfers,
+                    -5.0..=5.0,
+                ));
+
+                let travel = &mut self.persistent.travel;
+                ui.label("Minutes between buildings");
+                ui.add(egui::Slider::new(&mut travel.building, 0..=60));
+                ui.label("Minutes between campuses");
+                ui.add(egui::Slider::new(&mut travel.campus, 0..=180));
+
+                let (from, to, minutes) = &mut self.new_travel;
+                ui.label("From (campus or building code): ");
+                ui.text_edit_singleline(from);
+                ui.label("To (campus or building code): ");
+                ui.text_edit_singleline(to);
+                ui.add(egui::Slider::new(minutes, 0..=180));
+                if ui.button("Add Travel Time").clicked()
+                    && !from.trim().is_empty()
+                    && !to.trim().is_empty()
+                {
+                    travel.times.push(TravelTime {
+                        from: from.trim().to_owned(),
+                        to: to.trim().to_owned(),
+                        minutes: *minutes,
+                    });
+                }
+
+                let mut remove = None;
+                for (idx, time) in travel.times.iter().enumerate() {
+                    ui.label(time.to_string());
+
+                    if ui.button("Remove").clicked() {
+                        remove = Some(idx);
+                    }
+                }
+
+                if let Some(idx) = remove {
+                    travel.times.remove(idx);
+                }
             });
             if ui.button("Generate schedules").clicked() {
                 self.generate_schedules(100);
             }
             ui.label(format!(
-                "{} solutions found found in {:.2}ms ({} displayed)",
-                self.total_solutions,
+                "Best {} schedules found in {:.2}ms",
+                self.sorted_schedules.len(),
                 self.search_time.as_secs_f64() * 1000.0,
-                self.sorted_schedules.len()
             ));
+            if let Some(diagnosis) = &self.diagnosis {
+                ui.label(RichText::new(diagnosis).color(Color32::RED));
+            }
 
             let row_height = ui.text_style_height(&egui::TextStyle::Body);
             let total_rows = self.sorted_schedules.len();
@@ -251,7 +518,7 @@ impl eframe::App for ScheduleApp {
                                     course_type: None,
                                 }
                             }
-                            Include::Course { .. } => window.0 = Include::Class { crn: 0 },
+                            _ => window.0 = Include::Class { crn: 0 },
                         }
                     }
 
@@ -268,6 +535,7 @@ impl eframe::App for ScheduleApp {
                                 );
                             }
                         }
+                        Include::All | Include::AnyOf { .. } | Include::Optional { .. } => {}
                         Include::Course {
                             subject,
                             course_type,
@@ -295,8 +563,16 @@ impl eframe::App for ScheduleApp {
                         }
                     }
 
+                    ui.checkbox(&mut window.2, "Only if it fits");
+
                     if ui.button("Add").clicked() {
-                        self.persistent.includes.push(window.0);
+                        self.persistent.includes.push(if window.2 {
+                            Include::Optional {
+                                include: Box::new(window.0),
+                            }
+                        } else {
+                            window.0
+                        });
                     } else {
                         self.create_class_window = Some(window);
                     }
@@ -373,6 +649,69 @@ impl eframe::App for ScheduleApp {
                         window.1 = String::new();
                         window.2 = String::new();
                     }
+                    if ui
+                        .radio(matches!(window.0, Constraint::Credits { .. }), "Credits")
+                        .clicked()
+                    {
+                        window.0 = Constraint::Credits { min: 12, max: 18 };
+                        window.1 = String::new();
+                        window.2 = String::new();
+                    }
+                    if ui
+                        .radio(matches!(window.0, Constraint::Open), "Open Sections")
+                        .clicked()
+                    {
+                        window.0 = Constraint::Open;
+                        window.1 = String::new();
+                        window.2 = String::new();
+                    }
+                    if ui
+                        .radio(matches!(window.0, Constraint::WaitList { .. }), "Wait List")
+                        .clicked()
+                    {
+                        window.0 = Constraint::WaitList { max_ahead: 5 };
+                        window.1 = String::new();
+                        window.2 = String::new();
+                    }
+                    if ui
+                        .radio(
+                            matches!(window.0, Constraint::CrossListSeats),
+                            "Cross List Seats",
+                        )
+                        .clicked()
+                    {
+                        window.0 = Constraint::CrossListSeats;
+                        window.1 = String::new();
+                        window.2 = String::new();
+                    }
+                    if ui
+                        .radio(
+                            matches!(window.0, Constraint::Instructor { .. }),
+                            "Instructor",
+                        )
+                        .clicked()
+                    {
+                        window.0 = Constraint::Instructor {
+                            course: None,
+                            include: Vec::new(),
+                            exclude: Vec::new(),
+                        };
+                        window.1 = String::new();
+                        window.2 = String::new();
+                    }
+                    if ui
+                        .radio(
+                            matches!(window.0, Constraint::InstructionalMethod { .. }),
+                            "Instructional Method",
+                        )
+                        .clicked()
+                    {
+                        window.0 = Constraint::InstructionalMethod {
+                            allow: vec![Delivery::InPerson],
+                        };
+                        window.1 = String::new();
+                        window.2 = String::new();
+                    }
 
                     match &mut window.0 {
                         Constraint::BlockTimes { start, end, days } => {
@@ -403,9 +742,52 @@ impl eframe::App for ScheduleApp {
                             ui.label("Campus: ");
                             ui.text_edit_singleline(name);
                         }
+                        Constraint::Credits { min, max } => {
+                            ui.label("At least: ");
+                            ui.add(egui::Slider::new(min, 0..=24));
+                            ui.label("At most: ");
+                            ui.add(egui::Slider::new(max, 0..=24));
+                        }
+                        Constraint::WaitList { max_ahead } => {
+                            ui.label("At most ahead: ");
+                            ui.add(egui::Slider::new(max_ahead, 0..=50));
+                        }
+                        Constraint::Instructor { course, .. } => {
+                            ui.label("Course (empty for every course): ");
+                            ui.text_edit_singleline(course.get_or_insert_with(String::new));
+                            ui.label("Taught by one of (comma separated): ");
+                            ui.text_edit_singleline(&mut window.1);
+                            ui.label("Not taught by any of (comma separated): ");
+                            ui.text_edit_singleline(&mut window.2);
+                        }
+                        Constraint::InstructionalMethod { allow } => {
+                            for delivery in Delivery::ALL {
+                                let mut allowed = allow.contains(&delivery);
+                                if ui.checkbox(&mut allowed, delivery.to_string()).changed() {
+                                    allow.retain(|it| *it != delivery);
+                                    if allowed {
+                                        allow.push(delivery);
+                                    }
+                                }
+                            }
+                        }
+                        Constraint::Open | Constraint::CrossListSeats => {}
                     }
 
                     if ui.button("Add").clicked() {
+                        if let Constraint::Instructor {
+                            course,
+                            include,
+                            exclude,
+                        } = &mut window.0
+                        {
+                            *course = course
+                                .take()
+                                .map(|it| it.trim().to_uppercase())
+                                .filter(|it| !it.is_empty());
+                            *include = names(&window.1);
+                            *exclude = names(&window.2);
+                        }
                         self.persistent.constraints.push(window.0);
                     } else {
                         self.create_constraint_window = Some(window);
@@ -420,14 +802,60 @@ impl eframe::App for ScheduleApp {
         {
             let mut open = true;
             egui::Window::new("Schedule")
-                .id(Id::new(schedule))
+                .id(Id::new(
+                    schedule.iter().map(|class| class.crn).collect_vec(),
+                ))
                 .open(&mut open)
                 .show(ctx, |ui| {
                     ui.label(format!("Score: {:.2}, Breakdown: {:?}", score, breakdown));
 
-                    let (res, painter) =
+                    let (_res, painter) =
                         ui.allocate_painter(Vec2::new(500.0, 250.0), Sense::hover());
                     paint_schedule(&painter, schedule);
+
+                    ui.horizontal(|ui| {
+                        ui.label("Calendar: ");
+                        ui.text_edit_singleline(&mut self.ics_path);
+                        if ui.button("Export").clicked() {
+                            let classes = schedule.iter().collect_vec();
+                            let calendar = ics::calendar(&classes, Utc::now());
+                            self.export_status = Some(export(&self.ics_path, calendar));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Timetable: ");
+                        ui.text_edit_singleline(&mut self.html_path);
+                        if ui.button("Export").clicked() {
+                            // Alternates under the current plan, like solver-cli lists them
+                            let mut bank = self.raw_classes.clone();
+                            exclusion::apply(&mut bank, &self.persistent.exclusions);
+                            let prepared = self.persistent.prepare(&bank);
+                            let alts =
+                                solver::find_alts(&prepared.filtered, &schedule.iter().collect());
+
+                            let score = (*score, breakdown.clone());
+                            let page = html::page("Schedule", &[(&score, alts)]);
+                            self.export_status = Some(export(&self.html_path, page));
+                        }
+                    });
+                    match &self.export_status {
+                        Some(Ok(status)) => {
+                            ui.label(status);
+                        }
+                        Some(Err(error)) => {
+                            ui.label(RichText::new(error).color(Color32::RED));
+                        }
+                        None => {}
+                    }
+
+                    for class in schedule {
+                        for meeting in class.meetings.iter().filter(|it| it.excluded) {
+                            ui.label(format!(
+                                "Excluded: {} {} {}",
+                                class.crn, class.subject_course, meeting.meeting_type
+                            ));
+                        }
+                    }
                 });
 
             if !open {
@@ -474,13 +902,20 @@ fn time_selector(ui: &mut Ui, time: &mut Time, buffer: &mut String) {
 //todo rewrite using egui extras
 fn paint_schedule(painter: &Painter, schedule: &ScheduleOwned) {
     let rect = painter.clip_rect();
-    let (top_left, top_right, bottom_left, bottom_right) = (
+    let (top_left, top_right, bottom_left, _bottom_right) = (
         rect.left_top(),
         rect.right_top(),
         rect.left_bottom(),
         rect.right_bottom(),
     );
-    let (height, width) = (rect.height() - 20.0, rect.width() - 40.0);
+    let timetable = Timetable::new(&schedule.iter().collect_vec());
+    // Classes with meetings that have no set time go in a strip along the bottom
+    let strip = if timetable.untimed.is_empty() {
+        0.0
+    } else {
+        30.0
+    };
+    let (height, width) = (rect.height() - 20.0 - strip, rect.width() - 40.0);
     painter.rect(
         rect,
         Rounding::default(),
@@ -488,37 +923,12 @@ fn paint_schedule(painter: &Painter, schedule: &ScheduleOwned) {
         Stroke::none(),
     );
 
-    let mut start = None;
-    let mut end = None;
-    let mut data = HashMap::new();
-
-    for class in schedule {
-        for meeting in &class.meetings {
-            let start_time = meeting.start_time.unwrap();
-            let end_time = meeting.end_time.unwrap();
-
-            if start.is_none() {
-                start = Some(start_time);
-            }
-            start = start.min(Some(start_time));
-            end = end.max(Some(end_time));
-
-            for day in meeting.days.iter() {
-                data.entry(day).or_insert(Vec::new()).push((
-                    (class.crn, &class.subject_course),
-                    start_time,
-                    end_time,
-                ));
-            }
-        }
-    }
-
-    if let Some((start, end)) = start.zip(end) {
-        let start_hour = (start.hour as f32 + start.min as f32 / 60.0).floor() as u8;
-        let end_hour = (end.hour as f32 + end.min as f32 / 60.0).ceil() as u8;
+    if let Some((start_hour, end_hour)) = timetable.hours {
+        let height = height.min((end_hour - start_hour) as f32 * 50.0);
+        let grid = Rect::from_min_size(top_left + Vec2::new(40.0, 20.0), Vec2::new(width, height));
 
         for day in Days::everyday().iter() {
-            let offset = Vec2::new(day_to_idx(day) as f32 / 7.0 * width + 40.0, 0.0);
+            let offset = Vec2::new(day as usize as f32 / 7.0 * width + 40.0, 0.0);
             painter.line_segment(
                 [top_left + offset, bottom_left + offset],
                 Stroke::new(2.0, Color32::BLACK),
@@ -532,9 +942,8 @@ fn paint_schedule(painter: &Painter, schedule: &ScheduleOwned) {
             );
         }
 
-        for hour in start_hour..end_hour {
-            let block_height = (height / (end_hour - start_hour) as f32).min(50.0);
-            let offset = Vec2::new(0.0, (hour - start_hour) as f32 * block_height + 20.0);
+        for hour in timetable.hour_lines() {
+            let offset = Vec2::new(0.0, timetable.y(Time::new(hour, 0)) * height + 20.0);
             painter.line_segment(
                 [top_left + offset, top_right + offset],
                 Stroke::new(2.0, Color32::BLACK),
@@ -548,50 +957,76 @@ fn paint_schedule(painter: &Painter, schedule: &ScheduleOwned) {
             );
         }
 
-        for (day, meetings) in data {
-            let block_height = (height / (end_hour - start_hour) as f32).min(50.0);
-            let block_width = width / 7.0;
-
-            for ((crn, subject), class_start, class_end) in meetings {
-                let mut hasher = DefaultHasher::new();
-                crn.hash(&mut hasher);
-                let hash = hasher.finish();
-                let color = Color32::from_rgb(
-                    (hash >> 16 & 0xFF) as u8,
-                    (hash >> 8 & 0xFF) as u8,
-                    (hash & 0xFF) as u8,
-                );
+        for block in &timetable.blocks {
+            let [left, top, right, bottom] = block.rect(&timetable);
+            let min = grid.min + Vec2::new(left * grid.width(), top * grid.height());
+            let max = grid.min + Vec2::new(right * grid.width(), bottom * grid.height());
+            painter.rect_filled(
+                Rect::from_two_pos(min, max),
+                Rounding::none(),
+                class_color(block.class.crn),
+            );
+            painter.text(
+                min,
+                Align2::LEFT_TOP,
+                format!("{}\n{}", block.class.subject_course, block.class.crn),
+                FontId::default(),
+                Color32::BLACK,
+            );
+        }
+    }
 
-                let class_start = class_start.hour as f32 + class_start.min as f32 / 60.0;
-                let class_end = class_end.hour as f32 + class_end.min as f32 / 60.0;
-
-                let min = top_left
-                    + Vec2::new(
-                        day_to_idx(day) as f32 * block_width + 40.0,
-                        (class_start - start_hour as f32) * block_height + 20.0,
-                    );
-                let max = min + Vec2::new(block_width, block_height * (class_end - class_start));
-                painter.rect_filled(Rect::from_two_pos(min, max), Rounding::none(), color);
-                painter.text(
-                    min,
-                    Align2::LEFT_TOP,
-                    format!("{}\n{}", subject, crn),
-                    FontId::default(),
-                    Color32::BLACK,
-                );
-            }
+    if !timetable.untimed.is_empty() {
+        let top = bottom_left - Vec2::new(0.0, strip);
+        painter.line_segment(
+            [top, top + Vec2::new(rect.width(), 0.0)],
+            Stroke::new(2.0, Color32::BLACK),
+        );
+        painter.text(
+            top + Vec2::new(0.0, strip / 2.0),
+            Align2::LEFT_CENTER,
+            "No set time",
+            FontId::default(),
+            Color32::BLACK,
+        );
+
+        let block_width = (rect.width() - 90.0) / timetable.untimed.len() as f32;
+        for (idx, class) in timetable.untimed.iter().enumerate() {
+            let min = top + Vec2::new(90.0 + idx as f32 * block_width, 0.0);
+            let max = min + Vec2::new(block_width, strip);
+            painter.rect_filled(
+                Rect::from_two_pos(min, max),
+                Rounding::none(),
+                class_color(class.crn),
+            );
+            painter.text(
+                min,
+                Align2::LEFT_TOP,
+                format!("{} {}", class.subject_course, class.crn),
+                FontId::default(),
+                Color32::BLACK,
+            );
         }
     }
 }
 
-fn day_to_idx(day: Day) -> u8 {
-    match day {
-        Day::Sunday => 0,
-        Day::Monday => 1,
-        Day::Tuesday => 2,
-        Day::Wednesday => 3,
-        Day::Thursday => 4,
-        Day::Friday => 5,
-        Day::Saturday => 6,
+fn export(path: &str, contents: String) -> Result<String, String> {
+    match fs::write(path, contents) {
+        Ok(()) => Ok(format!("Wrote {}", path)),
+        Err(error) => Err(format!("Failed to write {}: {}", path, error)),
     }
 }
+
+fn class_color(crn: Crn) -> Color32 {
+    let [red, green, blue] = timetable::color(crn);
+    Color32::from_rgb(red, green, blue)
+}
+
+// Comma separated names, blanks dropped
+fn names(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_owned())
+        .collect()
+}
@@ -0,0 +1,214 @@
+use crate::link;
+use crate::plan::{Plan, Prepared};
+use crate::solver::{self, Constraint, CreditBounds, Include, Priorities, Variant};
+use crate::{Class, ClassBank, SmallClass};
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Why the solver came up empty
+#[derive(Clone, Debug, Serialize)]
+pub struct Diagnosis {
+    pub groups: Vec<GroupReport>,
+    /// Smallest set of includes that can't be scheduled together, dropping any one of them leaves
+    /// a schedule (empty when the includes can be scheduled, or a group was emptied before that)
+    pub conflicting: Vec<Include>,
+    /// Fewest and most credits the includes add up to, ignoring conflicts, when no amount in
+    /// between is allowed by the [`Constraint::Credits`]
+    pub credits: Option<(u64, u64)>,
+}
+
+/// What happened to the classes of one include on their way to the search
+#[derive(Clone, Debug, Serialize)]
+pub struct GroupReport {
+    pub include: Include,
+    /// Every schedule has to take it, it isn't part of an `AnyOf` or `Optional` include
+    pub required: bool,
+    pub matched: usize,
+    /// Classes removed by each constraint, the first one a class fails is blamed
+    pub constraints: Vec<(Constraint, usize)>,
+    /// Classes that made it to the search, sections with the same times count once
+    pub left: usize,
+}
+
+impl Diagnosis {
+    /// Nothing was emptied and nothing conflicts, schedules exist
+    pub fn is_feasible(&self) -> bool {
+        self.emptied().next().is_none() && self.conflicting.is_empty() && self.credits.is_none()
+    }
+
+    /// Required includes that lost all their classes
+    pub fn emptied(&self) -> impl Iterator<Item = &GroupReport> {
+        self.groups
+            .iter()
+            .filter(|group| group.required && group.left == 0)
+    }
+}
+
+/// Runs the same steps as the solver, keeping track of what every step removed
+///
+/// `bank` should already have the [`Plan::exclusions`] applied, like for [`Plan::solve`].
+pub fn diagnose(bank: &ClassBank, plan: &Plan) -> Diagnosis {
+    let Prepared {
+        included,
+        validated,
+        ..
+    } = plan.prepare(bank);
+    let includes = &plan.includes;
+    let constraints = &plan.constraints;
+
+    let groups = includes
+        .iter()
+        .flat_map(|include| include.leaves())
+        .unique()
+        .map(|include| {
+            let matched: &[&Class] = included.get(include).map(|it| it.as_slice()).unwrap_or(&[]);
+
+            let mut removed = vec![0; constraints.len()];
+            for class in matched {
+                if let Some(idx) = constraints.iter().position(|it| !it.allows(class)) {
+                    removed[idx] += 1;
+                }
+            }
+
+            GroupReport {
+                include: include.clone(),
+                required: includes.contains(include),
+                matched: matched.len(),
+                constraints: constraints.iter().cloned().zip(removed).collect(),
+                left: validated.get(include).map(|it| it.len()).unwrap_or(0),
+            }
+        })
+        .collect_vec();
+
+    // Only includes every schedule has to take can rule all of them out
+    let required = groups.iter().filter(|group| group.required).collect_vec();
+
+    let conflicting = if required.iter().any(|group| group.left == 0) {
+        Vec::new()
+    } else {
+        // Keep the includes in plan order so the result doesn't depend on hashing
+        let data = required
+            .iter()
+            .map(|group| {
+                let classes = validated[&group.include]
+                    .iter()
+                    .map(|class| SmallClass::new(class))
+                    .collect();
+                (group.include.clone(), classes)
+            })
+            .collect_vec();
+
+        minimal_conflict(data, bank, plan)
+    };
+
+    let credit_range = Include::selections(includes)
+        .into_iter()
+        .map(|(leaves, _)| {
+            leaves.into_iter().fold((0, 0), |(low, high), leaf| {
+                let ranges = validated
+                    .get(leaf)
+                    .iter()
+                    .flat_map(|classes| classes.iter())
+                    .map(|class| class.credit_hours.range())
+                    .collect_vec();
+                (
+                    low + ranges.iter().map(|it| it.0).min().unwrap_or(0),
+                    high + ranges.iter().map(|it| it.1).max().unwrap_or(0),
+                )
+            })
+        })
+        .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+        .unwrap_or((0, 0));
+    let credits = (!CreditBounds::of(constraints).allows(credit_range)).then_some(credit_range);
+
+    Diagnosis {
+        groups,
+        conflicting,
+        credits,
+    }
+}
+
+// Drops groups one at a time as long as what's left still has no schedule, pairing the links
+// and keeping to the travel times of the plan like the search
+fn minimal_conflict(
+    mut groups: Vec<(Include, Vec<SmallClass>)>,
+    bank: &ClassBank,
+    plan: &Plan,
+) -> Vec<Include> {
+    let feasible = |groups: &[(Include, Vec<SmallClass>)]| {
+        // The search has nothing to pick from, but taking nothing is always possible
+        if groups.is_empty() {
+            return true;
+        }
+
+        let mut variants = [Variant {
+            groups: groups.iter().map(|it| it.1.clone()).collect(),
+            optional: 0,
+        }];
+        link::link_variants(&mut variants, bank, &plan.links, &plan.travel);
+
+        let found = solver::top_variant_schedules(
+            &variants,
+            &Priorities::default(),
+            &plan.travel,
+            CreditBounds::ANY,
+            1,
+            1,
+        );
+        !found.is_empty()
+    };
+
+    if feasible(&groups) {
+        return Vec::new();
+    }
+
+    let mut idx = 0;
+    while idx < groups.len() {
+        let removed = groups.remove(idx);
+        if feasible(&groups) {
+            groups.insert(idx, removed);
+            idx += 1;
+        }
+    }
+
+    groups.into_iter().map(|(include, _)| include).collect()
+}
+
+impl Display for GroupReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} matched", self.include, self.matched)?;
+        for (constraint, removed) in &self.constraints {
+            if *removed > 0 {
+                write!(f, ", {removed} removed by {constraint}")?;
+            }
+        }
+        write!(f, ", {} left", self.left)
+    }
+}
+
+impl Display for Diagnosis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for group in self.emptied() {
+            writeln!(f, "No classes left for {group}")?;
+        }
+
+        if !self.conflicting.is_empty() {
+            writeln!(
+                f,
+                "These always conflict, drop one of them: {}",
+                self.conflicting.iter().join(", ")
+            )?;
+        }
+
+        if let Some((low, high)) = self.credits {
+            writeln!(f, "Schedules can only have {low}-{high} credits")?;
+        }
+
+        if self.is_feasible() {
+            writeln!(f, "Nothing rules out every schedule")?;
+        }
+
+        Ok(())
+    }
+}
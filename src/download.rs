@@ -0,0 +1,295 @@
+use anyhow::{bail, ensure, Context};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Clone, Debug)]
+pub struct DownloadOptions {
+    pub endpoint: String,
+    pub term: String,
+    pub cookies: String,
+    pub page_size: usize,
+    /// Time waited between two successful pages
+    pub delay: Duration,
+    /// How many times a page is retried before giving up
+    pub retries: u32,
+    /// Wait before the first retry, doubled on every retry after that
+    pub backoff: Duration,
+}
+
+impl DownloadOptions {
+    pub fn new(endpoint: String, term: String, cookies: String) -> Self {
+        Self {
+            endpoint,
+            term,
+            cookies,
+            page_size: 1000,
+            delay: Duration::from_secs(7),
+            retries: 5,
+            backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+/// What a download is up to, handed to the caller as it happens
+#[derive(Debug)]
+pub enum Progress {
+    /// Pages saved by an earlier run were picked up
+    Resumed { offset: usize },
+    /// A page came back with this many sections
+    Pulled { count: usize },
+    /// Banner ran out of sections before the total it reported
+    Empty,
+    /// A page failed and is tried again after `wait`
+    Retrying {
+        offset: usize,
+        error: anyhow::Error,
+        wait: Duration,
+        attempt: u32,
+        retries: u32,
+    },
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Progress::Resumed { offset } => write!(f, "Resuming from offset {offset}"),
+            Progress::Pulled { count } => write!(f, "Pulled {count}"),
+            Progress::Empty => write!(f, "Hit 0 len"),
+            Progress::Retrying {
+                offset,
+                error,
+                wait,
+                attempt,
+                retries,
+            } => write!(
+                f,
+                "Failed to pull offset {offset}: {error:#}, retrying in {:.1}s ({attempt}/{retries})",
+                wait.as_secs_f64()
+            ),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BasicStructure<'a> {
+    data: &'a [Value],
+}
+
+// One line of the progress file
+#[derive(Serialize, Deserialize)]
+struct Page {
+    offset: usize,
+    total: usize,
+    data: Vec<Value>,
+}
+
+/// Where pages are saved while a download is in progress
+pub fn progress_path(output: &Path) -> PathBuf {
+    let mut name = output.file_name().unwrap_or_default().to_os_string();
+    name.push(".pages");
+    output.with_file_name(name)
+}
+
+/// Pulls every section for the term into `output`, returns the downloaded sections
+///
+/// Pages are appended to the progress file (see [`progress_path`]) as they arrive so an
+/// interrupted download picks up from the last saved `pageOffset` when run again. `on_progress`
+/// hears about every page and retry.
+pub async fn download(
+    client: &Client,
+    options: &DownloadOptions,
+    output: &Path,
+    on_progress: &mut impl FnMut(Progress),
+) -> anyhow::Result<Vec<Value>> {
+    ensure!(options.page_size > 0, "Page size must be at least 1");
+    ensure!(
+        !output.exists(),
+        "{} already exists, remove it to download again",
+        output.display()
+    );
+
+    let progress = progress_path(output);
+    let (mut data, mut total) = read_progress(&progress).await?;
+    if !data.is_empty() {
+        on_progress(Progress::Resumed { offset: data.len() });
+    }
+
+    let mut progress_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&progress)
+        .await
+        .with_context(|| format!("Could not open {}", progress.display()))?;
+
+    while total.map(|total| data.len() < total).unwrap_or(true) {
+        let page = fetch_page_retrying(client, options, data.len(), on_progress).await?;
+
+        if page.data.is_empty() {
+            on_progress(Progress::Empty);
+            break;
+        }
+
+        on_progress(Progress::Pulled {
+            count: page.data.len(),
+        });
+
+        let mut line = serde_json::to_vec(&page).context("Could not serialize page")?;
+        line.push(b'\n');
+        progress_file
+            .write_all(&line)
+            .await
+            .context("Could not save page")?;
+        progress_file
+            .sync_data()
+            .await
+            .context("Could not save page")?;
+
+        total = Some(page.total);
+        data.extend(page.data);
+
+        if data.len() < page.total {
+            tokio::time::sleep(options.delay).await;
+        }
+    }
+
+    let structure = BasicStructure { data: &data };
+
+    let string = serde_json::to_string_pretty(&structure).context("Could not serialize data")?;
+    fs::write(output, string)
+        .await
+        .with_context(|| format!("Could not write {}", output.display()))?;
+    fs::remove_file(&progress)
+        .await
+        .with_context(|| format!("Could not remove {}", progress.display()))?;
+
+    Ok(data)
+}
+
+async fn read_progress(progress: &Path) -> anyhow::Result<(Vec<Value>, Option<usize>)> {
+    let mut data = Vec::new();
+    let mut total = None;
+
+    if !progress.exists() {
+        return Ok((data, total));
+    }
+
+    let contents = fs::read_to_string(progress)
+        .await
+        .with_context(|| format!("Could not read {}", progress.display()))?;
+
+    let mut valid_len = 0;
+    for line in contents.split_inclusive('\n') {
+        // A crash mid write leaves a partial last line, that page just gets fetched again
+        let Some(Ok(page)) = line.strip_suffix('\n').map(serde_json::from_str::<Page>) else {
+            break;
+        };
+
+        ensure!(
+            page.offset == data.len(),
+            "{} is out of order, expected offset {} but got {}",
+            progress.display(),
+            data.len(),
+            page.offset
+        );
+
+        valid_len += line.len();
+        total = Some(page.total);
+        data.extend(page.data);
+    }
+
+    // Drop whatever couldn't be parsed so the next page is appended on a fresh line
+    if valid_len != contents.len() {
+        fs::write(progress, &contents[..valid_len])
+            .await
+            .with_context(|| format!("Could not repair {}", progress.display()))?;
+    }
+
+    Ok((data, total))
+}
+
+async fn fetch_page_retrying(
+    client: &Client,
+    options: &DownloadOptions,
+    offset: usize,
+    on_progress: &mut impl FnMut(Progress),
+) -> anyhow::Result<Page> {
+    let mut backoff = options.backoff;
+    let mut attempt = 0;
+
+    loop {
+        match fetch_page(client, options, offset).await {
+            Ok(page) => return Ok(page),
+            Err(error) if attempt < options.retries => {
+                attempt += 1;
+                on_progress(Progress::Retrying {
+                    offset,
+                    error,
+                    wait: backoff,
+                    attempt,
+                    retries: options.retries,
+                });
+
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("Gave up on offset {offset} after {attempt} retries"))
+            }
+        }
+    }
+}
+
+async fn fetch_page(
+    client: &Client,
+    options: &DownloadOptions,
+    offset: usize,
+) -> anyhow::Result<Page> {
+    let response = client
+        .get(&options.endpoint)
+        .header("Cookie", &options.cookies)
+        .query(&[("txt_term", &options.term)])
+        .query(&[("pageOffset", &offset.to_string())])
+        .query(&[("pageMaxSize", &options.page_size.to_string())])
+        .query(&[("sortColumn", "subjectDescription")])
+        .query(&[("sortDirection", "asc")])
+        .send()
+        .await
+        .context("Request failed")?;
+
+    let status = response.status();
+    if status != StatusCode::OK {
+        bail!("Got status {status}");
+    }
+
+    let json: Value = response.json().await.context("Bad json")?;
+
+    let success = json
+        .get("success")
+        .and_then(|success| success.as_bool())
+        .unwrap_or_default();
+    ensure!(success, "Banner did not report success");
+
+    let data = json
+        .get("data")
+        .and_then(|data| data.as_array())
+        .context("Missing class data")?
+        .clone();
+
+    let total = json
+        .get("sectionsFetchedCount")
+        .and_then(|sections| sections.as_u64())
+        .context("Missing sectionsFetchedCount")? as usize;
+
+    Ok(Page {
+        offset,
+        total,
+        data,
+    })
+}
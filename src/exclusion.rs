@@ -0,0 +1,158 @@
+use crate::{parse_date, Class, ClassBank, Days, Schedule, Session, Time};
+use anyhow::{bail, Context};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Meetings that are kept on a class but left out of its schedule, so they never cause conflicts
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub enum Exclusion {
+    /// Meetings of this type (ex: `Exam`)
+    MeetingType { name: String },
+    /// Meetings that only run between these dates
+    Dates { start: NaiveDate, end: NaiveDate },
+    /// Meetings that fit inside this window, left out on these days alone
+    TimeWindow { start: Time, end: Time, days: Days },
+}
+
+impl Exclusion {
+    pub fn matches(&self, session: &Session) -> bool {
+        match self {
+            Exclusion::MeetingType { name } => session.meeting_type.eq_ignore_ascii_case(name),
+            Exclusion::Dates { start, end } => {
+                if let (Some(start_date), Some(end_date)) = (session.start_date, session.end_date) {
+                    start <= &start_date && &end_date <= end
+                } else {
+                    false
+                }
+            }
+            Exclusion::TimeWindow { start, end, days } => {
+                if session.days & *days == Days::never() {
+                    return false;
+                }
+
+                if let (Some(start_time), Some(end_time)) = (session.start_time, session.end_time) {
+                    start <= &start_time && &end_time <= end
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// Days a matching meeting is left out on, the rest of its days are kept
+    pub fn days(&self) -> Days {
+        match self {
+            Exclusion::TimeWindow { days, .. } => *days,
+            _ => Days::everyday(),
+        }
+    }
+}
+
+impl Display for Exclusion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Exclusion::MeetingType { name } => write!(f, "type:{name}"),
+            Exclusion::Dates { start, end } => write!(f, "dates:{start}..{end}"),
+            Exclusion::TimeWindow { start, end, days } if *days == Days::everyday() => {
+                write!(f, "time:{start}-{end}")
+            }
+            Exclusion::TimeWindow { start, end, days } => write!(f, "time:{start}-{end}@{days}"),
+        }
+    }
+}
+
+/// Parses `type:<name>`, `dates:<start>..<end>` and `time:<HHMM>-<HHMM>[@<days>]`,
+/// where days are letters from `UMTWRFS`
+impl FromStr for Exclusion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, rule) = s
+            .split_once(':')
+            .with_context(|| format!("Bad exclusion `{s}`, expected `<kind>:<rule>`"))?;
+
+        match kind {
+            "type" => Ok(Exclusion::MeetingType {
+                name: rule.to_owned(),
+            }),
+            "dates" => {
+                let (start, end) = rule
+                    .split_once("..")
+                    .with_context(|| format!("Bad date range `{rule}`"))?;
+
+                Ok(Exclusion::Dates {
+                    start: parse_date(start)?,
+                    end: parse_date(end)?,
+                })
+            }
+            "time" => {
+                let (window, days) = match rule.split_once('@') {
+                    Some((window, days)) => (window, Days::from_letters(days)?),
+                    None => (rule, Days::everyday()),
+                };
+                let (start, end) = window
+                    .split_once('-')
+                    .with_context(|| format!("Bad time window `{window}`"))?;
+
+                Ok(Exclusion::TimeWindow {
+                    start: start.parse()?,
+                    end: end.parse()?,
+                    days,
+                })
+            }
+            _ => bail!("Unknown exclusion kind `{kind}`, expected `type`, `dates` or `time`"),
+        }
+    }
+}
+
+/// Marks matching meetings as excluded and rebuilds the schedule, returns if anything changed
+///
+/// Meetings that were already excluded stay excluded. A meeting only matched on some of its days
+/// (ex: MWF by `time:0800-1000@F`) is split in two, with just those days excluded.
+pub fn apply_class(class: &mut Class, exclusions: &[Exclusion]) -> bool {
+    let mut changed = false;
+    let mut meetings = Vec::with_capacity(class.meetings.len());
+
+    for mut meeting in std::mem::take(&mut class.meetings) {
+        let days = exclusions
+            .iter()
+            .filter(|exclusion| !meeting.excluded && exclusion.matches(&meeting))
+            .fold(Days::never(), |days, exclusion| days | exclusion.days());
+
+        if days != Days::never() {
+            let kept = meeting.days & !days;
+            if kept != Days::never() {
+                meetings.push(Session {
+                    days: kept,
+                    ..meeting.clone()
+                });
+                meeting.days = meeting.days & days;
+            }
+
+            meeting.excluded = true;
+            changed = true;
+        }
+        meetings.push(meeting);
+    }
+    class.meetings = meetings;
+
+    if changed {
+        class.schedule = Schedule::from_meetings(&class.campus, &class.meetings);
+    }
+
+    changed
+}
+
+/// Applies the exclusions to every class in the bank, returns the number of excluded meetings
+pub fn apply(bank: &mut ClassBank, exclusions: &[Exclusion]) -> usize {
+    let mut excluded = 0;
+
+    for class in bank.values_mut() {
+        apply_class(class, exclusions);
+        excluded += class.meetings.iter().filter(|it| it.excluded).count();
+    }
+
+    excluded
+}
@@ -0,0 +1,366 @@
+use crate::Class;
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::str::FromStr;
+
+/// A condition on the sections of a course, written like
+/// `campus = "Boca Raton" and not (instructional_method ~ online or open)`
+///
+/// `=` compares ignoring case and `~` looks for the value anywhere in the field, fields with
+/// several values (faculty, buildings, ...) match when any of them does. Stored in plans as its
+/// text form.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Filter {
+    Is {
+        field: Field,
+        value: String,
+    },
+    Has {
+        field: Field,
+        value: String,
+    },
+    /// Open for registration
+    Open,
+    Not(Box<Filter>),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Field {
+    Campus,
+    InstructionalMethod,
+    ScheduleType,
+    SequenceNumber,
+    Faculty,
+    PartOfTerm,
+    SubjectCourse,
+    /// Code or name of the building of any meeting
+    Building,
+    Room,
+    MeetingType,
+}
+
+impl Field {
+    pub const ALL: [Field; 10] = [
+        Field::Campus,
+        Field::InstructionalMethod,
+        Field::ScheduleType,
+        Field::SequenceNumber,
+        Field::Faculty,
+        Field::PartOfTerm,
+        Field::SubjectCourse,
+        Field::Building,
+        Field::Room,
+        Field::MeetingType,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Campus => "campus",
+            Field::InstructionalMethod => "instructional_method",
+            Field::ScheduleType => "schedule_type",
+            Field::SequenceNumber => "sequence_number",
+            Field::Faculty => "faculty",
+            Field::PartOfTerm => "part_of_term",
+            Field::SubjectCourse => "subject_course",
+            Field::Building => "building",
+            Field::Room => "room",
+            Field::MeetingType => "meeting_type",
+        }
+    }
+
+    pub fn values(self, class: &Class) -> Vec<Cow<'_, str>> {
+        let meetings = || class.meetings.iter().filter(|it| !it.excluded);
+
+        match self {
+            Field::Campus => vec![class.campus.as_str().into()],
+            Field::InstructionalMethod => vec![class.instructional_method.as_str().into()],
+            Field::ScheduleType => vec![class.schedule_type.as_str().into()],
+            Field::SequenceNumber => vec![class.sequence_number.as_str().into()],
+            Field::Faculty => class
+                .faculty
+                .iter()
+                .map(|it| it.name.as_str().into())
+                .collect(),
+            Field::PartOfTerm => vec![class.part_of_term.as_str().into()],
+            Field::SubjectCourse => vec![class.subject_course.as_str().into()],
+            Field::Building => meetings()
+                .flat_map(|it| [&it.building_code, &it.building_name])
+                .flatten()
+                .map(|it| it.as_str().into())
+                .collect(),
+            Field::Room => meetings()
+                .filter_map(|it| it.room)
+                .map(|it| it.to_string().into())
+                .collect(),
+            Field::MeetingType => meetings()
+                .map(|it| it.meeting_type.as_str().into())
+                .collect(),
+        }
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Field {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Field::ALL
+            .into_iter()
+            .find(|field| field.name().eq_ignore_ascii_case(s))
+            .with_context(|| format!("Unknown field `{s}`"))
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, class: &Class) -> bool {
+        match self {
+            Filter::Is { field, value } => field
+                .values(class)
+                .iter()
+                .any(|it| it.eq_ignore_ascii_case(value)),
+            Filter::Has { field, value } => {
+                let value = value.to_lowercase();
+                field
+                    .values(class)
+                    .iter()
+                    .any(|it| it.to_lowercase().contains(&value))
+            }
+            Filter::Open => class.open,
+            Filter::Not(filter) => !filter.matches(class),
+            Filter::And(filters) => filters.iter().all(|it| it.matches(class)),
+            Filter::Or(filters) => filters.iter().any(|it| it.matches(class)),
+        }
+    }
+
+    // Or binds loosest, then and, then everything else
+    fn precedence(&self) -> u8 {
+        match self {
+            Filter::Or(_) => 0,
+            Filter::And(_) => 1,
+            _ => 2,
+        }
+    }
+
+    fn write_nested(&self, f: &mut Formatter<'_>, precedence: u8) -> std::fmt::Result {
+        if self.precedence() < precedence {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::Is { field, value } => write!(f, "{field} = {}", Quoted(value)),
+            Filter::Has { field, value } => write!(f, "{field} ~ {}", Quoted(value)),
+            Filter::Open => write!(f, "open"),
+            Filter::Not(filter) => {
+                write!(f, "not ")?;
+                filter.write_nested(f, 2)
+            }
+            Filter::And(filters) | Filter::Or(filters) => {
+                let (separator, precedence) = match self {
+                    Filter::And(_) => (" and ", 2),
+                    _ => (" or ", 1),
+                };
+
+                for (idx, filter) in filters.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(separator)?;
+                    }
+                    filter.write_nested(f, precedence)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+// Values are left bare when they read back as the same word
+struct Quoted<'a>(&'a str);
+
+impl Display for Quoted<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.0.is_empty() && self.0.chars().all(is_word) && !is_keyword(self.0) {
+            return f.write_str(self.0);
+        }
+
+        f.write_str("\"")?;
+        for char in self.0.chars() {
+            if char == '"' || char == '\\' {
+                f.write_str("\\")?;
+            }
+            write!(f, "{char}")?;
+        }
+        f.write_str("\"")
+    }
+}
+
+fn is_word(char: char) -> bool {
+    char.is_alphanumeric() || matches!(char, '_' | '-' | '.')
+}
+
+fn is_keyword(word: &str) -> bool {
+    ["and", "or", "not", "open"]
+        .iter()
+        .any(|it| it.eq_ignore_ascii_case(word))
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Is,
+    Has,
+    Word(String),
+    Quoted(String),
+}
+
+fn tokenize(s: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '=' => tokens.push(Token::Is),
+            '~' => tokens.push(Token::Has),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => value.extend(chars.next()),
+                        Some(char) => value.push(char),
+                        None => bail!("Unclosed quote in `{s}`"),
+                    }
+                }
+                tokens.push(Token::Quoted(value));
+            }
+            char if char.is_whitespace() => {}
+            char if is_word(char) => {
+                let mut word = char.to_string();
+                while let Some(char) = chars.next_if(|it| is_word(*it)) {
+                    word.push(char);
+                }
+                tokens.push(Token::Word(word));
+            }
+            char => bail!("Unexpected `{char}` in `{s}`"),
+        }
+    }
+
+    Ok(tokens)
+}
+
+type Tokens = Peekable<std::vec::IntoIter<Token>>;
+
+fn keyword(tokens: &mut Tokens, keyword: &str) -> bool {
+    tokens
+        .next_if(|it| matches!(it, Token::Word(word) if word.eq_ignore_ascii_case(keyword)))
+        .is_some()
+}
+
+fn parse_or(tokens: &mut Tokens) -> anyhow::Result<Filter> {
+    let mut filters = vec![parse_and(tokens)?];
+    while keyword(tokens, "or") {
+        filters.push(parse_and(tokens)?);
+    }
+
+    Ok(if filters.len() == 1 {
+        filters.remove(0)
+    } else {
+        Filter::Or(filters)
+    })
+}
+
+fn parse_and(tokens: &mut Tokens) -> anyhow::Result<Filter> {
+    let mut filters = vec![parse_unary(tokens)?];
+    while keyword(tokens, "and") {
+        filters.push(parse_unary(tokens)?);
+    }
+
+    Ok(if filters.len() == 1 {
+        filters.remove(0)
+    } else {
+        Filter::And(filters)
+    })
+}
+
+fn parse_unary(tokens: &mut Tokens) -> anyhow::Result<Filter> {
+    if keyword(tokens, "not") {
+        return Ok(Filter::Not(Box::new(parse_unary(tokens)?)));
+    }
+    if keyword(tokens, "open") {
+        return Ok(Filter::Open);
+    }
+
+    match tokens.next() {
+        Some(Token::Open) => {
+            let filter = parse_or(tokens)?;
+            match tokens.next() {
+                Some(Token::Close) => Ok(filter),
+                _ => bail!("Expected `)`"),
+            }
+        }
+        Some(Token::Word(field)) => {
+            let field = field.parse()?;
+            let op = tokens.next();
+            let value = match tokens.next() {
+                Some(Token::Word(value) | Token::Quoted(value)) => value,
+                _ => bail!("Expected a value after `{field}`"),
+            };
+
+            match op {
+                Some(Token::Is) => Ok(Filter::Is { field, value }),
+                Some(Token::Has) => Ok(Filter::Has { field, value }),
+                _ => bail!("Expected `=` or `~` after `{field}`"),
+            }
+        }
+        Some(token) => bail!("Unexpected {token:?}"),
+        None => bail!("Unexpected end of filter"),
+    }
+}
+
+impl FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = tokenize(s)?.into_iter().peekable();
+        let filter = parse_or(&mut tokens).with_context(|| format!("Bad filter `{s}`"))?;
+
+        if let Some(token) = tokens.next() {
+            bail!("Bad filter `{s}`, unexpected {token:?} after the end");
+        }
+
+        Ok(filter)
+    }
+}
+
+impl TryFrom<String> for Filter {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Filter> for String {
+    fn from(filter: Filter) -> Self {
+        filter.to_string()
+    }
+}
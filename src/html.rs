@@ -0,0 +1,232 @@
+use crate::solver::Priorities;
+use crate::timetable::{self, Timetable};
+use crate::{Class, CreditRange, Days, Time};
+use itertools::Itertools;
+use std::fmt::Write;
+
+// Sizes of the weekly grid in pixels
+const GUTTER: f32 = 50.0;
+const HEADER: f32 = 24.0;
+const GRID_WIDTH: f32 = 840.0;
+const HOUR_HEIGHT: f32 = 48.0;
+
+/// A schedule to render, its score and each class with its alternates (see
+/// [`crate::solver::find_alts`])
+pub type Shown<'a> = (&'a (f64, Priorities), Vec<(&'a Class, Vec<&'a Class>)>);
+
+/// A standalone HTML page with a weekly grid and details for each schedule, best first
+///
+/// Everything is inline so the file can be sent around and opened anywhere.
+pub fn page(title: &str, schedules: &[Shown]) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "<!DOCTYPE html>").unwrap();
+    writeln!(out, "<html lang=\"en\">").unwrap();
+    writeln!(out, "<head>").unwrap();
+    writeln!(out, "<meta charset=\"utf-8\">").unwrap();
+    writeln!(out, "<title>{}</title>", escape(title)).unwrap();
+    writeln!(out, "<style>{STYLE}</style>").unwrap();
+    writeln!(out, "</head>").unwrap();
+    writeln!(out, "<body>").unwrap();
+    writeln!(out, "<h1>{}</h1>", escape(title)).unwrap();
+
+    for (idx, ((score, breakdown), classes)) in schedules.iter().enumerate() {
+        write_schedule(&mut out, idx + 1, *score, breakdown, classes);
+    }
+    if schedules.is_empty() {
+        writeln!(out, "<p>No schedules</p>").unwrap();
+    }
+
+    writeln!(out, "</body>").unwrap();
+    writeln!(out, "</html>").unwrap();
+    out
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+section { margin-bottom: 3em; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; vertical-align: top; }
+td.number { text-align: right; }
+.swatch { display: inline-block; width: 0.8em; height: 0.8em; margin-right: 0.4em; }
+svg text { font-size: 11px; }
+";
+
+fn write_schedule(
+    out: &mut String,
+    rank: usize,
+    score: f64,
+    breakdown: &Priorities,
+    classes: &[(&Class, Vec<&Class>)],
+) {
+    let credits = CreditRange::of(classes.iter().map(|(class, _)| *class));
+
+    writeln!(out, "<section>").unwrap();
+    writeln!(out, "<h2>{rank}. Score {score:.2}</h2>").unwrap();
+    writeln!(out, "<p>Credits: {credits}</p>").unwrap();
+
+    let timetable = Timetable::new(&classes.iter().map(|(class, _)| *class).collect_vec());
+    write_grid(out, &timetable);
+    if !timetable.untimed.is_empty() {
+        let untimed = timetable
+            .untimed
+            .iter()
+            .map(|class| format!("{} {}", escape(&class.subject_course), class.crn))
+            .join(", ");
+        writeln!(out, "<p>No set time: {untimed}</p>").unwrap();
+    }
+
+    writeln!(out, "<table>").unwrap();
+    writeln!(
+        out,
+        "<tr><th>CRN</th><th>Course</th><th>Title</th><th>Instructor</th><th>Meetings</th><th>Alternates</th></tr>"
+    )
+    .unwrap();
+    for (class, alts) in classes {
+        let [red, green, blue] = timetable::color(class.crn);
+        let meetings = class
+            .meetings
+            .iter()
+            .map(|meeting| escape(&meeting.describe()))
+            .join("<br>");
+        let alts = alts
+            .iter()
+            .map(|alt| alt.crn)
+            .filter(|crn| *crn != class.crn)
+            .join(", ");
+
+        writeln!(
+            out,
+            "<tr><td><span class=\"swatch\" style=\"background: rgb({red}, {green}, {blue})\"></span>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{meetings}</td><td>{alts}</td></tr>",
+            class.crn,
+            escape(&class.subject_course),
+            escape(&class.name),
+            escape(&instructors(class)),
+        )
+        .unwrap();
+    }
+    writeln!(out, "</table>").unwrap();
+
+    writeln!(out, "<table>").unwrap();
+    writeln!(out, "<tr><th>Priority</th><th>Value</th></tr>").unwrap();
+    for (name, value) in breakdown.terms() {
+        writeln!(
+            out,
+            "<tr><td>{name}</td><td class=\"number\">{value:.2}</td></tr>"
+        )
+        .unwrap();
+    }
+    writeln!(out, "</table>").unwrap();
+    writeln!(out, "</section>").unwrap();
+}
+
+// The timetable as an SVG, each meeting clipped to its own nested SVG with the details on hover
+fn write_grid(out: &mut String, timetable: &Timetable) {
+    let Some((first, last)) = timetable.hours else {
+        return;
+    };
+    let grid_height = (last - first) as f32 * HOUR_HEIGHT;
+    let (width, height) = (GUTTER + GRID_WIDTH, HEADER + grid_height);
+    let column = GRID_WIDTH / 7.0;
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<rect width=\"{width}\" height=\"{height}\" fill=\"#eee\"/>"
+    )
+    .unwrap();
+
+    for day in Days::everyday().iter() {
+        let x = GUTTER + day as usize as f32 * column;
+        writeln!(
+            out,
+            "<line x1=\"{x}\" y1=\"0\" x2=\"{x}\" y2=\"{height}\" stroke=\"#999\"/>"
+        )
+        .unwrap();
+        writeln!(out, "<text x=\"{}\" y=\"16\">{day:?}</text>", x + 4.0).unwrap();
+    }
+    for hour in timetable.hour_lines() {
+        let y = HEADER + timetable.y(Time::new(hour, 0)) * grid_height;
+        writeln!(
+            out,
+            "<line x1=\"0\" y1=\"{y}\" x2=\"{width}\" y2=\"{y}\" stroke=\"#999\"/>"
+        )
+        .unwrap();
+        writeln!(out, "<text x=\"4\" y=\"{}\">{hour}:00</text>", y + 12.0).unwrap();
+    }
+
+    for block in &timetable.blocks {
+        let [left, top, right, bottom] = block.rect(timetable);
+        let (x, y) = (GUTTER + left * GRID_WIDTH, HEADER + top * grid_height);
+        let (block_width, block_height) =
+            ((right - left) * GRID_WIDTH, (bottom - top) * grid_height);
+        let [red, green, blue] = timetable::color(block.class.crn);
+        // Dark text on light colors and the other way around
+        let light = 0.299 * red as f32 + 0.587 * green as f32 + 0.114 * blue as f32 > 140.0;
+        let text = if light { "#000" } else { "#fff" };
+
+        let mut lines = vec![
+            block.class.subject_course.clone(),
+            block.class.crn.to_string(),
+        ];
+        lines.extend(block.meeting.location());
+        let instructors = instructors(block.class);
+        if !instructors.is_empty() {
+            lines.push(instructors);
+        }
+
+        writeln!(
+            out,
+            "<svg x=\"{x}\" y=\"{y}\" width=\"{block_width}\" height=\"{block_height}\">"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "<title>{} {}\n{}</title>",
+            escape(&block.class.subject_course),
+            escape(&block.class.name),
+            escape(&block.meeting.describe())
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "<rect width=\"100%\" height=\"100%\" fill=\"rgb({red}, {green}, {blue})\" stroke=\"#333\"/>"
+        )
+        .unwrap();
+        for (idx, line) in lines.iter().enumerate() {
+            writeln!(
+                out,
+                "<text x=\"3\" y=\"{}\" fill=\"{text}\">{}</text>",
+                12 + idx * 13,
+                escape(line)
+            )
+            .unwrap();
+        }
+        writeln!(out, "</svg>").unwrap();
+    }
+
+    writeln!(out, "</svg>").unwrap();
+}
+
+fn instructors(class: &Class) -> String {
+    class.faculty.iter().map(|it| it.name.as_str()).join("; ")
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            char => out.push(char),
+        }
+    }
+    out
+}
@@ -0,0 +1,155 @@
+use crate::{Class, Day, Session};
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use itertools::Itertools;
+use std::fmt::Write;
+
+/// An RFC 5545 calendar with a weekly event for every meeting of `classes`
+///
+/// Times are left floating, so they show in whatever time zone the calendar is opened in. Meetings
+/// without set times or dates and excluded meetings are left out. `stamp` is when the calendar was
+/// made (ex: [`Utc::now`]).
+pub fn calendar(classes: &[&Class], stamp: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//schedual//schedual//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+    ];
+
+    for class in classes {
+        for (idx, meeting) in class.meetings.iter().enumerate() {
+            if !meeting.excluded {
+                lines.extend(event(class, idx, meeting, stamp));
+            }
+        }
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    let mut out = String::new();
+    for line in lines {
+        fold(&mut out, &line);
+    }
+    out
+}
+
+fn event(class: &Class, idx: usize, meeting: &Session, stamp: DateTime<Utc>) -> Vec<String> {
+    let (Some((start_time, end_time)), Some(start_date), Some(end_date)) =
+        (meeting.times(), meeting.start_date, meeting.end_date)
+    else {
+        return Vec::new();
+    };
+    // The first day it actually meets on
+    let Some(first) = start_date
+        .iter_days()
+        .take(7)
+        .find(|date| {
+            meeting
+                .days
+                .iter()
+                .any(|day| weekday(day) == date.weekday())
+        })
+        .filter(|date| *date <= end_date)
+    else {
+        return Vec::new();
+    };
+
+    let by_day = meeting
+        .days
+        .iter()
+        .map(|day| by_day(weekday(day)))
+        .join(",");
+    let location = meeting.location();
+    let instructors = class.faculty.iter().map(|it| it.name.as_str()).join("; ");
+
+    let mut description = format!("CRN: {}", class.crn);
+    if !instructors.is_empty() {
+        write!(description, "\nInstructor: {instructors}").unwrap();
+    }
+    if let Some(location) = &location {
+        write!(description, "\nLocation: {location}").unwrap();
+    }
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_owned(),
+        format!("UID:{}-{}-{idx}@schedual", class.term, class.crn),
+        format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")),
+        format!("DTSTART:{}T{start_time}00", date(first)),
+        format!("DTEND:{}T{end_time}00", date(first)),
+        format!(
+            "RRULE:FREQ=WEEKLY;BYDAY={by_day};UNTIL={}T235959",
+            date(end_date)
+        ),
+        format!(
+            "SUMMARY:{}",
+            escape(&format!(
+                "{} {} ({})",
+                class.subject_course, class.name, meeting.meeting_type
+            ))
+        ),
+    ];
+    if let Some(location) = &location {
+        lines.push(format!("LOCATION:{}", escape(location)));
+    }
+    lines.push(format!("DESCRIPTION:{}", escape(&description)));
+    lines.push("END:VEVENT".to_owned());
+    lines
+}
+
+fn date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+fn weekday(day: Day) -> Weekday {
+    match day {
+        Day::Sunday => Weekday::Sun,
+        Day::Monday => Weekday::Mon,
+        Day::Tuesday => Weekday::Tue,
+        Day::Wednesday => Weekday::Wed,
+        Day::Thursday => Weekday::Thu,
+        Day::Friday => Weekday::Fri,
+        Day::Saturday => Weekday::Sat,
+    }
+}
+
+fn by_day(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Sun => "SU",
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+    }
+}
+
+// Text values escape backslashes, commas, semicolons and newlines
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '\\' | ';' | ',' => {
+                out.push('\\');
+                out.push(char);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            char => out.push(char),
+        }
+    }
+    out
+}
+
+// Lines are at most 75 octets, continued on the next line after a space
+fn fold(out: &mut String, line: &str) {
+    let mut width = 0;
+    for char in line.chars() {
+        if width + char.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(char);
+        width += char.len_utf8();
+    }
+    out.push_str("\r\n");
+}
@@ -1,32 +1,108 @@
+pub mod banner;
+pub mod diagnosis;
+pub mod download;
+pub mod exclusion;
+pub mod filter;
+pub mod html;
+pub mod ics;
+pub mod link;
+pub mod mask;
+pub mod plan;
+pub mod report;
 pub mod solver;
+pub mod term;
+pub mod text;
+pub mod timetable;
+pub mod travel;
 
-use anyhow::{ensure, Context};
+use anyhow::{bail, ensure, Context};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
 use std::ops::{BitAnd, BitOr, Not};
 use std::str::FromStr;
 
+// Start minute, duration, the dates the meeting runs between and where it meets
+pub type Block = (u16, u16, DateRange, Place);
+
 #[derive(Default, Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Schedule {
-    data: [SmallVec<[(u16, u16); 10]>; 7],
+    #[serde(deserialize_with = "stored_blocks::deserialize")]
+    data: [SmallVec<[Block; 10]>; 7],
+}
+
+// Banks converted before blocks carried dates store plain `(start, duration)` pairs, and ones
+// converted before they carried places leave them out
+mod stored_blocks {
+    use crate::{Block, DateRange, Place};
+    use serde::{Deserialize, Deserializer};
+    use smallvec::SmallVec;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredBlock {
+        Placed(u16, u16, DateRange, Place),
+        Dated(u16, u16, DateRange),
+        Undated(u16, u16),
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[SmallVec<[Block; 10]>; 7], D::Error> {
+        let days = <[Vec<StoredBlock>; 7]>::deserialize(deserializer)?;
+
+        Ok(days.map(|blocks| {
+            blocks
+                .into_iter()
+                .map(|block| match block {
+                    StoredBlock::Placed(start, duration, dates, place) => {
+                        (start, duration, dates, place)
+                    }
+                    StoredBlock::Dated(start, duration, dates) => {
+                        (start, duration, dates, Place::NONE)
+                    }
+                    StoredBlock::Undated(start, duration) => {
+                        (start, duration, DateRange::ALWAYS, Place::NONE)
+                    }
+                })
+                .collect()
+        }))
+    }
 }
 
 impl Schedule {
+    /// Meetings for the whole term with no known place
     pub fn generate(times: &[(Days, Time, Time)]) -> Self {
-        let mut schedule = Schedule::default();
+        Self::generate_dated(
+            &times
+                .iter()
+                .map(|(days, start, end)| (*days, *start, *end, DateRange::ALWAYS))
+                .collect::<Vec<_>>(),
+        )
+    }
 
-        for (days, start, end) in times {
-            if *start == Time::new(19, 10) && *end == Time::new(20, 30) {
-                continue;
-            }
+    /// Meetings that only run between their dates, with no known place
+    pub fn generate_dated(times: &[(Days, Time, Time, DateRange)]) -> Self {
+        Self::generate_at(
+            &times
+                .iter()
+                .map(|(days, start, end, dates)| (*days, *start, *end, *dates, Place::NONE))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn generate_at(times: &[(Days, Time, Time, DateRange, Place)]) -> Self {
+        let mut schedule = Schedule::default();
 
+        for (days, start, end, dates, place) in times {
             let start_time = start.hour as u16 * 60 + start.min as u16;
             let duration =
                 (end.hour - start.hour) as i16 * 60 + (end.min as i16 - start.min as i16);
 
-            let val = (start_time, duration as u16);
+            let val = (start_time, duration as u16, *dates, *place);
 
             for day in days.iter() {
                 let idx = match day {
@@ -46,11 +122,34 @@ impl Schedule {
         schedule
     }
 
+    /// Builds the schedule of every meeting with a set time that isn't excluded, for a class on
+    /// `campus`
+    pub fn from_meetings(campus: &str, meetings: &[Session]) -> Self {
+        Self::generate_at(
+            &meetings
+                .iter()
+                .filter(|it| !it.excluded)
+                .flat_map(|it| {
+                    if let (Some(s), Some(e)) = (it.start_time, it.end_time) {
+                        let place = Place::of(campus, it.building_code.as_deref());
+                        Some((it.days, s, e, it.dates(), place))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+
     pub fn overlaps(&self, others: &[&Self]) -> bool {
         for (day, self_day) in self.data.iter().enumerate() {
             for other in others {
                 for b in &other.data[day] {
                     for a in self_day {
+                        if !a.2.overlaps(&b.2) {
+                            continue;
+                        }
+
                         if ((a.0)..=(a.0 + a.1)).contains(&b.0) {
                             return true;
                         }
@@ -65,6 +164,16 @@ impl Schedule {
         false
     }
 
+    /// Meets at the same times on the same dates, wherever that is
+    pub fn same_times(&self, other: &Self) -> bool {
+        self.data.iter().zip(&other.data).all(|(a, b)| {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|(a, b)| (a.0, a.1, a.2) == (b.0, b.1, b.2))
+        })
+    }
+
     pub fn clear(&mut self) {
         for times in &mut self.data {
             times.clear();
@@ -80,7 +189,7 @@ impl Schedule {
     }
 
     pub fn flatten(from: &[&Self]) -> Self {
-        const VEC: SmallVec<[(u16, u16); 10]> = SmallVec::new_const();
+        const VEC: SmallVec<[Block; 10]> = SmallVec::new_const();
         let mut data = [VEC; 7];
 
         for entry in from {
@@ -98,8 +207,8 @@ impl Schedule {
         }
     }
 
-    pub fn data(&self) -> [&[(u16, u16)]; 7] {
-        let mut data: [&[(u16, u16)]; 7] = [&[]; 7];
+    pub fn data(&self) -> [&[Block]; 7] {
+        let mut data: [&[Block]; 7] = [&[]; 7];
 
         for (day, times) in self.data.iter().enumerate() {
             data[day] = times.as_slice();
@@ -140,12 +249,114 @@ pub struct Class {
     pub schedule: Schedule,
 }
 
+impl Class {
+    /// Any of the instructors has `name` somewhere in their name, ignoring case
+    pub fn taught_by(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.faculty
+            .iter()
+            .any(|faculty| faculty.name.to_lowercase().contains(&name))
+    }
+
+    /// Open with seats left
+    pub fn has_seats(&self) -> bool {
+        self.open && self.enrollment.available > 0
+    }
+
+    /// Percent of seats still free, counting the cross list when it is fuller
+    pub fn free_seats(&self) -> u32 {
+        let percent = |available: i64, capacity: u64| {
+            u64::checked_div(available.max(0) as u64 * 100, capacity).map_or(0, |it| it.min(100))
+                as u32
+        };
+
+        let section = percent(self.enrollment.available, self.enrollment.capacity);
+        match &self.cross_list {
+            Some(cross_list) => section.min(percent(
+                cross_list.cross_list_available,
+                cross_list.cross_list_capacity,
+            )),
+            None => section,
+        }
+    }
+
+    pub fn delivery(&self) -> Delivery {
+        let method = self.instructional_method.to_lowercase();
+
+        if ["hybrid", "blended", "reduced seat"]
+            .iter()
+            .any(|it| method.contains(it))
+        {
+            Delivery::Hybrid
+        } else if ["online", "distance", "remote"]
+            .iter()
+            .any(|it| method.contains(it))
+        {
+            Delivery::Online
+        } else {
+            Delivery::InPerson
+        }
+    }
+}
+
+/// How a section is taught, read from [`Class::instructional_method`]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub enum Delivery {
+    Online,
+    Hybrid,
+    InPerson,
+}
+
+impl Delivery {
+    pub const ALL: [Delivery; 3] = [Delivery::InPerson, Delivery::Hybrid, Delivery::Online];
+}
+
+impl Display for Delivery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Delivery::Online => write!(f, "online"),
+            Delivery::Hybrid => write!(f, "hybrid"),
+            Delivery::InPerson => write!(f, "in person"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct SmallClass {
     pub crn: Crn,
+    /// Sections taken along with this one, see [`link::Link`]
+    #[serde(default)]
+    pub linked: Vec<Crn>,
+    /// Fewest and most credit hours, counting the linked sections
+    #[serde(default)]
+    pub credits: (u64, u64),
+    /// Percent of seats still free, the fullest of the linked sections
+    #[serde(default)]
+    pub free_seats: u32,
+    /// Sections taught by a preferred instructor, see [`solver::mark_preferred`]
+    #[serde(default)]
+    pub preferred: u32,
     pub schedule: Schedule,
 }
 
+impl SmallClass {
+    pub fn new(class: &Class) -> Self {
+        Self {
+            crn: class.crn,
+            linked: Vec::new(),
+            credits: class.credit_hours.range(),
+            free_seats: class.free_seats(),
+            preferred: 0,
+            schedule: class.schedule.to_owned(),
+        }
+    }
+
+    /// This section's CRN followed by the linked ones
+    pub fn crns(&self) -> impl Iterator<Item = Crn> + '_ {
+        std::iter::once(self.crn).chain(self.linked.iter().copied())
+    }
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct CreditHours {
     pub credit_hour_high: Option<u64>,
@@ -153,6 +364,46 @@ pub struct CreditHours {
     pub credit_hours: Option<u64>,
 }
 
+impl CreditHours {
+    /// Fewest and most credits the section can be taken for, variable credit sections only set
+    /// `credit_hour_low` and `credit_hour_high`
+    pub fn range(&self) -> (u64, u64) {
+        let low = self
+            .credit_hour_low
+            .or(self.credit_hours)
+            .or(self.credit_hour_high)
+            .unwrap_or(0);
+        let high = self.credit_hour_high.or(self.credit_hours).unwrap_or(low);
+
+        (low, high.max(low))
+    }
+}
+
+/// Fewest and most credits a set of sections adds up to, written as `low` or `low-high`
+#[derive(Copy, Clone, Debug, Default, Serialize, Eq, PartialEq)]
+pub struct CreditRange(pub u64, pub u64);
+
+impl CreditRange {
+    pub fn of<'a>(classes: impl IntoIterator<Item = &'a Class>) -> Self {
+        classes
+            .into_iter()
+            .map(|class| class.credit_hours.range())
+            .fold(Self(0, 0), |Self(low, high), it| {
+                Self(low + it.0, high + it.1)
+            })
+    }
+}
+
+impl Display for CreditRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0 == self.1 {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "{}-{}", self.0, self.1)
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct CrossList {
     pub cross_list: u64,
@@ -180,8 +431,10 @@ pub struct Session {
     // TODO represent time better
     pub start_time: Option<Time>,
     pub end_time: Option<Time>,
-    pub start_date: String,
-    pub end_date: String,
+    #[serde(with = "session_date")]
+    pub start_date: Option<NaiveDate>,
+    #[serde(with = "session_date")]
+    pub end_date: Option<NaiveDate>,
     pub days: Days,
 
     pub building_code: Option<String>,
@@ -189,6 +442,140 @@ pub struct Session {
     pub room: Option<u64>,
 
     pub meeting_type: String,
+    /// Left out of the class schedule by an [`exclusion::Exclusion`]
+    #[serde(default)]
+    pub excluded: bool,
+}
+
+impl Session {
+    /// The dates the meeting runs between, unknown ends are left open
+    pub fn dates(&self) -> DateRange {
+        DateRange {
+            start: self.start_date.unwrap_or(NaiveDate::MIN),
+            end: self.end_date.unwrap_or(NaiveDate::MAX),
+        }
+    }
+
+    /// Start and end time, `None` for meetings with no set time (ex: asynchronous online)
+    pub fn times(&self) -> Option<(Time, Time)> {
+        self.start_time.zip(self.end_time)
+    }
+
+    /// Building and room (ex: `SE 101`), the building's code when it has one
+    pub fn location(&self) -> Option<String> {
+        let building = self.building_code.as_ref().or(self.building_name.as_ref());
+
+        match (building, self.room) {
+            (Some(building), Some(room)) => Some(format!("{building} {room}")),
+            (Some(building), None) => Some(building.clone()),
+            (None, Some(room)) => Some(room.to_string()),
+            (None, None) => None,
+        }
+    }
+
+    /// One line for the meeting (ex: `Class MWF 0900-0950 SE 101, 2023-08-21 to 2023-12-08`)
+    pub fn describe(&self) -> String {
+        let mut text = self.meeting_type.clone();
+        match self.times() {
+            Some((start, end)) => text.push_str(&format!(" {} {start}-{end}", self.days)),
+            None => text.push_str(" no set time"),
+        }
+        if let Some(location) = self.location() {
+            text.push_str(&format!(" {location}"));
+        }
+        if let (Some(start), Some(end)) = (self.start_date, self.end_date) {
+            text.push_str(&format!(", {start} to {end}"));
+        }
+        if self.excluded {
+            text.push_str(" (excluded)");
+        }
+        text
+    }
+}
+
+/// Campus and building of a meeting, hashed so blocks stay small, `0` when it isn't known
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub struct Place {
+    pub campus: u32,
+    pub building: u32,
+}
+
+impl Place {
+    pub const NONE: Place = Place {
+        campus: 0,
+        building: 0,
+    };
+
+    pub fn of(campus: &str, building: Option<&str>) -> Self {
+        Self {
+            campus: Self::key(campus),
+            building: building.map(Self::key).unwrap_or(0),
+        }
+    }
+
+    /// Hash of a campus name or building code, ignoring case
+    pub fn key(name: &str) -> u32 {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() {
+            0
+        } else {
+            fxhash::hash32(&name).max(1)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    pub const ALWAYS: DateRange = DateRange {
+        start: NaiveDate::MIN,
+        end: NaiveDate::MAX,
+    };
+
+    pub fn overlaps(&self, other: &DateRange) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+impl Default for DateRange {
+    fn default() -> Self {
+        DateRange::ALWAYS
+    }
+}
+
+/// Parses Banner's `MM/DD/YYYY` dates as well as ISO 8601 ones
+pub fn parse_date(date: &str) -> anyhow::Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%m/%d/%Y")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .with_context(|| format!("Bad date: {date}"))
+}
+
+// Dates are written as ISO 8601, banks converted before dates were parsed still hold Banner's format
+mod session_date {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        date: &Option<NaiveDate>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        date.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<NaiveDate>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(date) if !date.is_empty() => super::parse_date(&date)
+                .map(Some)
+                .map_err(serde::de::Error::custom),
+            _ => Ok(None),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
@@ -263,6 +650,26 @@ impl Days {
         }
     }
 
+    /// Parses day letters as used by registrars, `U` for Sunday and `R` for Thursday (ex: `MWF`)
+    pub fn from_letters(letters: &str) -> anyhow::Result<Self> {
+        let mut days = Days::never();
+
+        for letter in letters.chars() {
+            match letter.to_ascii_uppercase() {
+                'U' => days.sunday = true,
+                'M' => days.monday = true,
+                'T' => days.tuesday = true,
+                'W' => days.wednesday = true,
+                'R' => days.thursday = true,
+                'F' => days.friday = true,
+                'S' => days.saturday = true,
+                _ => bail!("Bad day `{letter}`, expected one of `UMTWRFS`"),
+            }
+        }
+
+        Ok(days)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = Day> {
         let mut vec = Vec::new();
 
@@ -351,6 +758,20 @@ pub enum Day {
     Saturday,
 }
 
+impl Day {
+    pub fn letter(&self) -> char {
+        match self {
+            Day::Sunday => 'U',
+            Day::Monday => 'M',
+            Day::Tuesday => 'T',
+            Day::Wednesday => 'W',
+            Day::Thursday => 'R',
+            Day::Friday => 'F',
+            Day::Saturday => 'S',
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
 pub struct Time {
     pub hour: u8,
@@ -361,11 +782,30 @@ impl Time {
     pub fn new(hour: u8, min: u8) -> Self {
         Self { hour, min }
     }
+
+    /// Minutes since midnight
+    pub fn minutes(self) -> u16 {
+        self.hour as u16 * 60 + self.min as u16
+    }
 }
 
-impl ToString for Time {
-    fn to_string(&self) -> String {
-        format!("{}{}", self.hour, self.min)
+/// Day letters, `U` for Sunday and `R` for Thursday (ex: `MWF`)
+impl Display for Days {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for day in self.iter() {
+            write!(f, "{}", day.letter())?;
+        }
+        Ok(())
+    }
+}
+
+/// Banner's `HHMM`, or `HH:MM` with the alternate flag (`{:#}`)
+impl Display for Time {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match f.alternate() {
+            true => write!(f, "{:02}:{:02}", self.hour, self.min),
+            false => write!(f, "{:02}{:02}", self.hour, self.min),
+        }
     }
 }
 
@@ -0,0 +1,157 @@
+use crate::solver::{Include, Variant};
+use crate::travel::{Travel, TravelTimes};
+use crate::{Class, ClassBank, Crn, Schedule, SmallClass};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Sections that have to be taken together, ex: a `PHY2048` lecture with one of its `PHY2048L` labs
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Link {
+    pub lead: Include,
+    pub follow: Include,
+    pub rule: LinkRule,
+}
+
+/// How the sections of a [`Link`] are paired up
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub enum LinkRule {
+    /// Sections whose sequence numbers start with the same `len` characters (ex: `01` with `01A`
+    /// and `01B` for a `len` of 2)
+    SequencePrefix { len: usize },
+    /// Only the listed pairs
+    Table { pairs: Vec<LinkPair> },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct LinkPair {
+    pub lead: Crn,
+    pub follow: Vec<Crn>,
+}
+
+impl Link {
+    pub fn pairs(&self, lead: &Class, follow: &Class) -> bool {
+        match &self.rule {
+            LinkRule::SequencePrefix { len } => match (prefix(lead, *len), prefix(follow, *len)) {
+                (Some(lead), Some(follow)) => lead == follow,
+                _ => false,
+            },
+            LinkRule::Table { pairs } => pairs
+                .iter()
+                .any(|pair| pair.lead == lead.crn && pair.follow.contains(&follow.crn)),
+        }
+    }
+
+    /// Sections with the same key pair with the same sections, `None` when the link doesn't
+    /// cover the class
+    pub fn key(&self, class: &Class) -> Option<String> {
+        if !self.lead.matches(class) && !self.follow.matches(class) {
+            return None;
+        }
+
+        Some(match &self.rule {
+            LinkRule::SequencePrefix { len } => prefix(class, *len).unwrap_or_default().to_owned(),
+            LinkRule::Table { .. } => class.crn.to_string(),
+        })
+    }
+}
+
+fn prefix(class: &Class, len: usize) -> Option<&str> {
+    class.sequence_number.get(..len)
+}
+
+impl Display for Link {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} with {}", self.lead, self.follow)?;
+
+        match &self.rule {
+            LinkRule::SequencePrefix { len } => write!(f, " by the first {len} of the sequence"),
+            LinkRule::Table { pairs } => write!(f, " by {} listed pairs", pairs.len()),
+        }
+    }
+}
+
+/// Merges the lead and follow groups of every link into one group of the pairs that go together
+///
+/// A pair is taken as a single section, listing the follow CRNs in [`SmallClass::linked`]. Pairs
+/// that conflict, or are too close to get between with the `travel` times, are left out. Links
+/// whose groups aren't both in a variant are skipped.
+pub fn link_variants(
+    variants: &mut [Variant],
+    bank: &ClassBank,
+    links: &[Link],
+    travel: &TravelTimes,
+) {
+    let travel = Travel::new(travel);
+    for variant in variants {
+        for link in links {
+            link_groups(&mut variant.groups, bank, link, &travel);
+        }
+    }
+}
+
+fn link_groups(groups: &mut Vec<Vec<SmallClass>>, bank: &ClassBank, link: &Link, travel: &Travel) {
+    let Some(lead) = find_group(groups, bank, &link.lead, None) else {
+        return;
+    };
+    let Some(follow) = find_group(groups, bank, &link.follow, Some(lead)) else {
+        return;
+    };
+
+    let mut paired = Vec::new();
+    for lead_section in &groups[lead] {
+        let lead_class = section(lead_section, bank, &link.lead).expect("Checked by find_group");
+
+        for follow_section in &groups[follow] {
+            let follow_class =
+                section(follow_section, bank, &link.follow).expect("Checked by find_group");
+
+            if !link.pairs(lead_class, follow_class)
+                || travel.conflicts(&lead_section.schedule, &[&follow_section.schedule])
+            {
+                continue;
+            }
+
+            let mut linked = lead_section.linked.clone();
+            linked.extend(follow_section.crns());
+
+            paired.push(SmallClass {
+                crn: lead_section.crn,
+                linked,
+                credits: (
+                    lead_section.credits.0 + follow_section.credits.0,
+                    lead_section.credits.1 + follow_section.credits.1,
+                ),
+                free_seats: lead_section.free_seats.min(follow_section.free_seats),
+                preferred: lead_section.preferred + follow_section.preferred,
+                schedule: Schedule::flatten(&[&lead_section.schedule, &follow_section.schedule]),
+            });
+        }
+    }
+
+    groups[lead] = paired;
+    groups.remove(follow);
+}
+
+// First group, other than `skip`, where every section has a class matching the include
+fn find_group(
+    groups: &[Vec<SmallClass>],
+    bank: &ClassBank,
+    include: &Include,
+    skip: Option<usize>,
+) -> Option<usize> {
+    (0..groups.len()).find(|idx| {
+        Some(*idx) != skip
+            && !groups[*idx].is_empty()
+            && groups[*idx]
+                .iter()
+                .all(|it| section(it, bank, include).is_some())
+    })
+}
+
+// The class of a possibly already linked section that matches the include
+fn section<'a>(section: &SmallClass, bank: &'a ClassBank, include: &Include) -> Option<&'a Class> {
+    section
+        .crns()
+        .filter_map(|crn| bank.get(&crn))
+        .find(|class| include.matches(class))
+}
@@ -0,0 +1,146 @@
+use crate::{Block, DateRange, Place, Schedule};
+use smallvec::SmallVec;
+
+/// Minutes each slot of a [`ScheduleMask`] covers
+pub const SLOT: u16 = 5;
+/// Slots in a day
+pub const SLOTS: usize = 24 * 60 / SLOT as usize;
+const WORDS: usize = SLOTS.div_ceil(64);
+
+/// The slots taken on each day of the week, Sunday first
+type Week = [[u64; WORDS]; 7];
+
+/// A [`Schedule`] as bitmasks of five minute slots, so checking for overlaps is a bitwise and
+///
+/// Meetings on different dates (ex: half term sections) get their own masks and only conflict
+/// when their dates overlap. A meeting takes every slot from the one it starts in through the one
+/// it ends in, so like [`Schedule::overlaps`] one ending as another starts counts as overlapping.
+/// Schedules with meetings off the five minute grid (ex: 9:00-9:52) have no mask, they'd conflict
+/// with meetings in the same slot that they don't overlap.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ScheduleMask {
+    masks: SmallVec<[(DateRange, Week); 1]>,
+}
+
+impl ScheduleMask {
+    /// `None` when a meeting starts or ends off the five minute grid, check those with
+    /// [`Schedule::overlaps`] instead
+    pub fn new(schedule: &Schedule) -> Option<Self> {
+        let mut mask = Self::default();
+        for (day, blocks) in schedule.data.iter().enumerate() {
+            for block in blocks {
+                if block.0 % SLOT != 0 || block.1 % SLOT != 0 {
+                    return None;
+                }
+
+                let week = mask.week(block.2);
+                let first = (block.0 / SLOT) as usize;
+                let last = ((block.0 + block.1) / SLOT) as usize;
+                for slot in first..=last.min(SLOTS - 1) {
+                    week[day][slot / 64] |= 1 << (slot % 64);
+                }
+            }
+        }
+
+        Some(mask)
+    }
+
+    // The masks for meetings on `dates`, added when there aren't any yet
+    fn week(&mut self, dates: DateRange) -> &mut Week {
+        let idx = match self.masks.iter().position(|(it, _)| *it == dates) {
+            Some(idx) => idx,
+            None => {
+                self.masks.push((dates, [[0; WORDS]; 7]));
+                self.masks.len() - 1
+            }
+        };
+        &mut self.masks[idx].1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.masks
+            .iter()
+            .flat_map(|(_, week)| week)
+            .flatten()
+            .all(|it| *it == 0)
+    }
+
+    pub fn overlaps(&self, others: &[&Self]) -> bool {
+        others.iter().any(|other| {
+            self.masks.iter().any(|(dates, week)| {
+                other
+                    .masks
+                    .iter()
+                    .filter(|(other_dates, _)| dates.overlaps(other_dates))
+                    .any(|(_, other_week)| shares_slots(week, other_week))
+            })
+        })
+    }
+
+    /// Takes the slots of `other` too
+    pub fn insert(&mut self, other: &Self) {
+        for (dates, other_week) in &other.masks {
+            let week = self.week(*dates);
+            for (a, b) in week.iter_mut().flatten().zip(other_week.iter().flatten()) {
+                *a |= b;
+            }
+        }
+    }
+
+    pub fn flatten(from: &[&Self]) -> Self {
+        let mut mask = Self::default();
+        for other in from {
+            mask.insert(other);
+        }
+        mask
+    }
+
+    /// Back to intervals for scoring and rendering, a block for each run of slots
+    ///
+    /// Places aren't kept, and meetings on the same dates that touch or overlap come back as one
+    /// block.
+    pub fn to_schedule(&self) -> Schedule {
+        let mut schedule = Schedule::default();
+
+        for (dates, week) in &self.masks {
+            for (day, words) in week.iter().enumerate() {
+                let taken = |slot: usize| words[slot / 64] & (1 << (slot % 64)) != 0;
+
+                let mut slot = 0;
+                while slot < SLOTS {
+                    if !taken(slot) {
+                        slot += 1;
+                        continue;
+                    }
+
+                    let first = slot;
+                    while slot + 1 < SLOTS && taken(slot + 1) {
+                        slot += 1;
+                    }
+                    let block: Block = (
+                        first as u16 * SLOT,
+                        (slot - first) as u16 * SLOT,
+                        *dates,
+                        Place::NONE,
+                    );
+                    schedule.data[day].push(block);
+                    slot += 1;
+                }
+            }
+        }
+
+        schedule.sort();
+        schedule
+    }
+}
+
+// Without branching, so the whole week is checked in a few vector instructions
+fn shares_slots(a: &Week, b: &Week) -> bool {
+    let mut shared = 0;
+    for day in 0..7 {
+        for word in 0..WORDS {
+            shared |= a[day][word] & b[day][word];
+        }
+    }
+    shared != 0
+}
@@ -0,0 +1,163 @@
+use crate::exclusion::Exclusion;
+use crate::link::{self, Link};
+use crate::solver::{
+    self, Classes, Constraint, CreditBounds, Filters, Include, PreferredInstructor, Priorities,
+    Variant,
+};
+use crate::travel::TravelTimes;
+use crate::{ClassBank, Crn};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Everything needed to ask the solver for schedules, shared between solver-cli and solver-gui
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Plan {
+    pub includes: Vec<Include>,
+    pub constraints: Vec<Constraint>,
+    pub priorities: Priorities,
+    /// Who [`Priorities::preferred_instructor`] rewards
+    pub instructors: Vec<PreferredInstructor>,
+    /// Time it takes to get between places, meetings closer than this never go together
+    pub travel: TravelTimes,
+    /// Meetings left out of class schedules on top of the ones excluded when the bank was built
+    pub exclusions: Vec<Exclusion>,
+    /// Sections of different includes that have to be taken together
+    pub links: Vec<Link>,
+    /// Which sections of a course can be picked, ex: `PHY2048 = 'campus = "Boca Raton"'`
+    pub filters: Filters,
+    pub results: usize,
+}
+
+impl Default for Plan {
+    fn default() -> Self {
+        Self {
+            includes: Vec::new(),
+            constraints: Vec::new(),
+            priorities: Priorities::default(),
+            instructors: Vec::new(),
+            travel: TravelTimes::default(),
+            exclusions: Vec::new(),
+            links: Vec::new(),
+            filters: Filters::new(),
+            results: 3,
+        }
+    }
+}
+
+/// The classes of a plan at every step on their way to the search, see [`Plan::prepare`]
+#[derive(Clone, Debug)]
+pub struct Prepared<'a> {
+    /// Classes matching each include and its filters
+    pub included: Classes<'a>,
+    /// Included classes that every constraint allows, where [`solver::find_alts`] looks
+    pub filtered: Classes<'a>,
+    /// Filtered classes less the ones meeting at the same times as another
+    pub validated: Classes<'a>,
+    /// Linked and marked groups, before sections that can't fit are pruned
+    pub linked: Vec<Variant>,
+    /// The linked groups less what [`solver::prune_conflicts`] rules out, what gets searched
+    pub variants: Vec<Variant>,
+}
+
+/// The best schedules of a plan, see [`Plan::solve`]
+#[derive(Clone, Debug)]
+pub struct Solution<'a> {
+    pub prepared: Prepared<'a>,
+    pub schedules: Vec<((f64, Priorities), Vec<Crn>)>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PlanFormat {
+    Toml,
+    Json,
+}
+
+impl PlanFormat {
+    /// Picks the format from the file extension, anything that isn't `.json` is read as TOML
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|it| it.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => PlanFormat::Json,
+            _ => PlanFormat::Toml,
+        }
+    }
+}
+
+impl Plan {
+    /// Runs every step before the search on `bank`, which should already have the
+    /// [`Plan::exclusions`] applied
+    pub fn prepare<'a>(&'a self, bank: &'a ClassBank) -> Prepared<'a> {
+        let included = solver::include_classes(bank, &self.includes, &self.filters);
+        let filtered = solver::filter_classes(included.clone(), &self.constraints);
+        let validated =
+            solver::validate_linked_classes(filtered.clone(), &self.links, &self.travel);
+
+        let mut variants = solver::map_variants(&validated, &self.includes);
+        link::link_variants(&mut variants, bank, &self.links, &self.travel);
+        solver::mark_preferred(&mut variants, bank, &self.instructors);
+        let linked = variants.clone();
+        for variant in &mut variants {
+            variant.groups = solver::prune_conflicts(std::mem::take(&mut variant.groups));
+        }
+
+        Prepared {
+            included,
+            filtered,
+            validated,
+            linked,
+            variants,
+        }
+    }
+
+    /// Best `count` schedules of `bank` for the plan, searching with `threads` threads
+    pub fn solve<'a>(&'a self, bank: &'a ClassBank, count: usize, threads: usize) -> Solution<'a> {
+        let prepared = self.prepare(bank);
+        let schedules = solver::top_variant_schedules(
+            &prepared.variants,
+            &self.priorities,
+            &self.travel,
+            CreditBounds::of(&self.constraints),
+            count,
+            threads,
+        );
+
+        Solution {
+            prepared,
+            schedules,
+        }
+    }
+
+    pub fn parse(data: &str, format: PlanFormat) -> anyhow::Result<Self> {
+        match format {
+            PlanFormat::Toml => toml::from_str(data).context("Bad TOML plan"),
+            PlanFormat::Json => serde_json::from_str(data).context("Bad JSON plan"),
+        }
+    }
+
+    pub fn to_string(&self, format: PlanFormat) -> anyhow::Result<String> {
+        match format {
+            PlanFormat::Toml => toml::to_string_pretty(self).context("Could not write TOML plan"),
+            PlanFormat::Json => {
+                serde_json::to_string_pretty(self).context("Could not write JSON plan")
+            }
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        Self::parse(&data, PlanFormat::from_path(path))
+            .with_context(|| format!("Could not load plan {}", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+
+        let data = self.to_string(PlanFormat::from_path(path))?;
+        fs::write(path, data).with_context(|| format!("Could not write {}", path.display()))
+    }
+}
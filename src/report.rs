@@ -0,0 +1,226 @@
+use crate::solver::Priorities;
+use crate::{Class, CreditRange, Crn, Session};
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use itertools::Itertools;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use std::fmt::{Display, Formatter, Write};
+
+/// How solved schedules are printed
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    /// Timetable grids meant for reading
+    #[default]
+    Table,
+    Json,
+    /// One row per class
+    Csv,
+    Markdown,
+}
+
+/// A solved schedule with everything about it worth printing
+#[derive(Clone, Debug, Serialize)]
+pub struct Ranked {
+    /// Starting from 1 for the best schedule
+    pub rank: usize,
+    pub score: f64,
+    pub breakdown: Breakdown,
+    /// Fewest and most credit hours
+    pub credits: CreditRange,
+    pub classes: Vec<RankedClass>,
+}
+
+/// The unweighted parts of a score, see [`Priorities::terms`]
+#[derive(Clone, Debug)]
+pub struct Breakdown(pub Priorities);
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RankedClass {
+    pub crn: Crn,
+    pub course: String,
+    pub title: String,
+    pub meetings: Vec<Meeting>,
+    /// Other sections of the course meeting at the same times
+    pub alts: Vec<Crn>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Meeting {
+    pub meeting_type: String,
+    /// Day letters (ex: `MWF`)
+    pub days: String,
+    /// `HHMM`, left out for meetings without set times
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    /// See [`Session::location`]
+    pub location: Option<String>,
+    pub excluded: bool,
+    /// The whole meeting on one line, see [`Session::describe`]
+    pub description: String,
+}
+
+impl Ranked {
+    /// `alts` is the schedule paired with its alternates, see [`crate::solver::find_alts`]
+    pub fn new(
+        rank: usize,
+        (score, breakdown): &(f64, Priorities),
+        alts: &[(&Class, Vec<&Class>)],
+    ) -> Self {
+        Self {
+            rank,
+            score: *score,
+            breakdown: Breakdown(breakdown.clone()),
+            credits: CreditRange::of(alts.iter().map(|(class, _)| *class)),
+            classes: alts
+                .iter()
+                .map(|(class, alts)| RankedClass {
+                    crn: class.crn,
+                    course: class.subject_course.clone(),
+                    title: class.name.clone(),
+                    meetings: class.meetings.iter().map(Meeting::new).collect(),
+                    alts: alts
+                        .iter()
+                        .map(|alt| alt.crn)
+                        .filter(|crn| *crn != class.crn)
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Meeting {
+    pub fn new(session: &Session) -> Self {
+        let times = session.times();
+
+        Self {
+            meeting_type: session.meeting_type.clone(),
+            days: session.days.to_string(),
+            start: times.map(|it| it.0.to_string()),
+            end: times.map(|it| it.1.to_string()),
+            start_date: session.start_date,
+            end_date: session.end_date,
+            location: session.location(),
+            excluded: session.excluded,
+            description: session.describe(),
+        }
+    }
+}
+
+impl Serialize for Breakdown {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let terms = self.0.terms();
+        let mut map = serializer.serialize_map(Some(terms.len()))?;
+        for (name, value) in terms {
+            map.serialize_entry(name, &value)?;
+        }
+        map.end()
+    }
+}
+
+impl Display for Meeting {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.description)
+    }
+}
+
+pub fn json(ranked: &[Ranked]) -> anyhow::Result<String> {
+    Ok(serde_json::to_string_pretty(ranked)?)
+}
+
+/// A header then one row per class, with the schedule's rank, score, credits and breakdown
+/// repeated on each
+pub fn csv(ranked: &[Ranked]) -> String {
+    let terms = Priorities::default().terms().map(|(name, _)| name);
+    let mut out = String::new();
+
+    let header = ["rank", "score", "credits"]
+        .into_iter()
+        .chain(terms)
+        .chain(["crn", "course", "title", "meetings", "alts"]);
+    writeln!(out, "{}", header.map(csv_field).join(",")).unwrap();
+
+    for schedule in ranked {
+        for class in &schedule.classes {
+            let row = [
+                schedule.rank.to_string(),
+                schedule.score.to_string(),
+                schedule.credits.to_string(),
+            ]
+            .into_iter()
+            .chain(
+                schedule
+                    .breakdown
+                    .0
+                    .terms()
+                    .map(|(_, value)| value.to_string()),
+            )
+            .chain([
+                class.crn.to_string(),
+                class.course.clone(),
+                class.title.clone(),
+                class.meetings.iter().join("; "),
+                class.alts.iter().join(" "),
+            ]);
+            writeln!(out, "{}", row.map(|it| csv_field(&it)).join(",")).unwrap();
+        }
+    }
+
+    out
+}
+
+// Quoted when it holds a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// A section per schedule with its breakdown and a table of classes
+pub fn markdown(ranked: &[Ranked]) -> String {
+    let mut out = String::new();
+
+    for schedule in ranked {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        writeln!(out, "## {}. Score {:.2}", schedule.rank, schedule.score).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "Credits: {}", schedule.credits).unwrap();
+        writeln!(out).unwrap();
+
+        writeln!(out, "| Priority | Value |").unwrap();
+        writeln!(out, "| --- | ---: |").unwrap();
+        for (name, value) in schedule.breakdown.0.terms() {
+            writeln!(out, "| {name} | {value:.2} |").unwrap();
+        }
+        writeln!(out).unwrap();
+
+        writeln!(out, "| CRN | Course | Title | Meetings | Alternates |").unwrap();
+        writeln!(out, "| --- | --- | --- | --- | --- |").unwrap();
+        for class in &schedule.classes {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                class.crn,
+                markdown_cell(&class.course),
+                markdown_cell(&class.title),
+                markdown_cell(&class.meetings.iter().join("<br>")),
+                class.alts.iter().join(", "),
+            )
+            .unwrap();
+        }
+    }
+
+    out
+}
+
+// Pipes would start a new cell
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
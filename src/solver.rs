@@ -1,28 +1,42 @@
-use crate::{Class, ClassBank, Crn, Days, Schedule, SmallClass, Time};
+use crate::filter::Filter;
+use crate::link::Link;
+use crate::travel::{Travel, TravelTimes};
+use crate::{Block, Class, ClassBank, Crn, Days, Delivery, Place, Schedule, SmallClass, Time};
 use fxhash::FxHashMap as HashMap;
 use fxhash::FxHashSet as HashSet;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Mutex;
+use std::thread;
 
 //type Classes = HashMap<Include, Vec<Class>>;
-type Classes<'a> = HashMap<&'a Include, Vec<&'a Class>>;
+/// Sections kept for each include
+pub type Classes<'a> = HashMap<&'a Include, Vec<&'a Class>>;
 type ClassesMapped = Vec<SmallClass>;
+/// Filters on the sections of a course, keyed by its subject and course number
+pub type Filters = BTreeMap<String, Filter>;
+
+pub type ScheduleOwned = Vec<Class>;
 
 pub fn include_classes<'a>(
     classes: &'a ClassBank,
     includes: &'a [Include],
-    filters: HashMap<String, Box<dyn Fn(&Class) -> bool>>,
+    filters: &Filters,
 ) -> Classes<'a> {
     let mut filtered_classes: Classes = HashMap::default();
 
     classes
-        .iter()
-        .filter_map(|(_, class)| {
-            for include in includes {
+        .values()
+        .filter_map(|class| {
+            for include in includes.iter().flat_map(|it| it.leaves()) {
                 if include.matches(class)
                     && filters
                         .get(&class.subject_course)
-                        .map(|filter| (filter)(class))
+                        .map(|filter| filter.matches(class))
                         .unwrap_or(true)
                 {
                     return Some((include, class));
@@ -54,77 +68,1079 @@ pub fn filter_classes<'a>(mut classes: Classes<'a>, constraints: &[Constraint])
     classes
 }
 
-pub fn validate_classes(mut classes: Classes) -> Classes {
+/// Drops sections that meet at the same times as one already kept
+///
+/// Sections with meetings that have no set time are kept, those meetings never conflict.
+pub fn validate_classes(classes: Classes) -> Classes {
+    validate_linked_classes(classes, &[], &TravelTimes::default())
+}
+
+/// Same as [`validate_classes`], only dropping sections of a [`Link`] as duplicates when they
+/// also pair with the same sections, and sections in different places when getting there takes
+/// any time
+pub fn validate_linked_classes<'a>(
+    mut classes: Classes<'a>,
+    links: &[Link],
+    travel: &TravelTimes,
+) -> Classes<'a> {
+    let places = !travel.is_empty();
+
     classes.values_mut().for_each(|class_group| {
         let mut seen = HashSet::default();
 
         class_group.retain(|class| {
-            let mut layout = Vec::new();
-
-            for meeting in &class.meetings {
-                layout.push((meeting.start_time, meeting.end_time, meeting.days));
-
-                if meeting.start_time.is_none() {
-                    return false;
-                }
-                if meeting.end_time.is_none() {
-                    return false;
-                }
-            }
-
-            if !seen.insert(layout) {
-                return false;
-            }
+            let layout = class
+                .meetings
+                .iter()
+                .filter(|it| !it.excluded)
+                .map(|meeting| {
+                    (
+                        meeting.start_time,
+                        meeting.end_time,
+                        meeting.days,
+                        meeting.dates(),
+                        places.then(|| Place::of(&class.campus, meeting.building_code.as_deref())),
+                    )
+                })
+                .collect_vec();
+            let keys = links.iter().map(|link| link.key(class)).collect_vec();
 
-            true
+            seen.insert((layout, keys))
         });
     });
 
     classes
 }
 
-pub fn map_classes(classes: Classes) -> Vec<ClassesMapped> {
-    classes
+/// Every meeting that isn't excluded has a start and end time
+pub fn has_times(class: &Class) -> bool {
+    class
+        .meetings
+        .iter()
+        .filter(|it| !it.excluded)
+        .all(|meeting| meeting.start_time.is_some() && meeting.end_time.is_some())
+}
+
+/// The groups to search for one way of picking from [`Include::AnyOf`] and [`Include::Optional`]
+#[derive(Clone, Debug)]
+pub struct Variant {
+    pub groups: Vec<ClassesMapped>,
+    /// Optional courses this variant takes
+    pub optional: usize,
+}
+
+/// One group per include in every [`Include::selections`] of `includes`, a group is left empty
+/// when nothing was kept for its include
+pub fn map_variants(classes: &Classes, includes: &[Include]) -> Vec<Variant> {
+    Include::selections(includes)
         .into_iter()
-        .map(|(_include, group)| {
-            group
+        .map(|(leaves, optional)| Variant {
+            groups: leaves
                 .into_iter()
-                .map(|it| SmallClass {
-                    crn: it.crn,
-                    schedule: it.schedule.to_owned(),
+                .map(|leaf| {
+                    classes
+                        .get(leaf)
+                        .iter()
+                        .flat_map(|group| group.iter())
+                        .map(|it| SmallClass::new(it))
+                        .collect_vec()
                 })
-                .collect_vec()
+                .collect_vec(),
+            optional,
         })
         .collect_vec()
 }
 
+/// Counts the sections (linked ones included) taught by a preferred instructor into
+/// [`SmallClass::preferred`]
+pub fn mark_preferred(
+    variants: &mut [Variant],
+    bank: &ClassBank,
+    instructors: &[PreferredInstructor],
+) {
+    for section in variants
+        .iter_mut()
+        .flat_map(|variant| variant.groups.iter_mut())
+        .flatten()
+    {
+        section.preferred = section
+            .crns()
+            .filter_map(|crn| bank.get(&crn))
+            .filter(|class| instructors.iter().any(|it| it.matches(class)))
+            .count() as u32;
+    }
+}
+
+pub fn map_classes(classes: Classes) -> Vec<ClassesMapped> {
+    classes
+        .into_values()
+        .map(|group| group.into_iter().map(SmallClass::new).collect_vec())
+        .collect_vec()
+}
+
 pub fn bruteforce_schedules<'a, F: FnMut(&[Crn], &[&'a Schedule])>(
     data: &'a [ClassesMapped],
     classes: &mut Vec<Crn>,
     schedule: &mut Vec<&'a Schedule>,
     callback: &mut F,
+) {
+    bruteforce_counted(
+        data,
+        classes,
+        schedule,
+        callback,
+        &mut SearchStats::default(),
+    );
+}
+
+/// Same as [`bruteforce_schedules`], also counting the work done
+pub fn bruteforce_stats<'a, F: FnMut(&[Crn], &[&'a Schedule])>(
+    data: &'a [ClassesMapped],
+    callback: &mut F,
+) -> SearchStats {
+    let mut stats = SearchStats::default();
+    bruteforce_counted(data, &mut Vec::new(), &mut Vec::new(), callback, &mut stats);
+    stats
+}
+
+fn bruteforce_counted<'a, F: FnMut(&[Crn], &[&'a Schedule])>(
+    data: &'a [ClassesMapped],
+    classes: &mut Vec<Crn>,
+    schedule: &mut Vec<&'a Schedule>,
+    callback: &mut F,
+    stats: &mut SearchStats,
 ) {
     for choice in data.first().iter().flat_map(|it| it.iter()) {
-        if !choice.schedule.overlaps(&schedule) {
-            classes.push(choice.crn);
+        if !choice.schedule.overlaps(schedule) {
+            stats.nodes += 1;
+            let len = classes.len();
+            classes.extend(choice.crns());
             schedule.push(&choice.schedule);
 
             if data.len() <= 1 {
                 // Leaf
-                (callback)(&classes, &schedule);
+                stats.solutions += 1;
+                (callback)(classes, schedule);
             } else {
-                bruteforce_schedules(&data[1..], classes, schedule, callback);
+                bruteforce_counted(&data[1..], classes, schedule, callback, stats);
             }
 
-            classes.pop();
+            classes.truncate(len);
             schedule.pop();
         }
     }
 }
 
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchStats {
+    /// Sections placed into a partial schedule
+    pub nodes: u64,
+    pub solutions: u64,
+}
+
+/// Which pairs of sections from different groups can't be taken together
+#[derive(Clone, Debug)]
+pub struct ConflictGraph {
+    // Index of each group's first section in `rows`
+    offsets: Vec<usize>,
+    // One bit per section, set when the two conflict
+    rows: Vec<Vec<u64>>,
+}
+
+impl ConflictGraph {
+    pub fn new(data: &[ClassesMapped]) -> Self {
+        let offsets = data
+            .iter()
+            .scan(0, |offset, group| {
+                let start = *offset;
+                *offset += group.len();
+                Some(start)
+            })
+            .collect_vec();
+        let sections = data.iter().flatten().collect_vec();
+        let words = sections.len().div_ceil(64);
+
+        let mut rows = vec![vec![0; words]; sections.len()];
+        for (group_a, start_a) in offsets.iter().enumerate() {
+            for (group_b, start_b) in offsets.iter().enumerate().skip(group_a + 1) {
+                for (a, section_a) in data[group_a].iter().enumerate() {
+                    for (b, section_b) in data[group_b].iter().enumerate() {
+                        if section_a.schedule.overlaps(&[&section_b.schedule]) {
+                            let (a, b) = (start_a + a, start_b + b);
+                            rows[a][b / 64] |= 1 << (b % 64);
+                            rows[b][a / 64] |= 1 << (a % 64);
+                        }
+                    }
+                }
+            }
+        }
+
+        Self { offsets, rows }
+    }
+
+    pub fn conflicts(&self, group_a: usize, a: usize, group_b: usize, b: usize) -> bool {
+        let (a, b) = (self.offsets[group_a] + a, self.offsets[group_b] + b);
+        self.rows[a][b / 64] & (1 << (b % 64)) != 0
+    }
+
+    /// Drops sections that conflict with every remaining section of some other group, until
+    /// nothing changes
+    pub fn prune(&self, domains: &mut [Vec<usize>]) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for group in 0..domains.len() {
+                for other in 0..domains.len() {
+                    if group == other {
+                        continue;
+                    }
+
+                    let (before, others) = (domains[group].len(), &domains[other]);
+                    let kept = domains[group]
+                        .iter()
+                        .copied()
+                        .filter(|a| others.iter().any(|b| !self.conflicts(group, *a, other, *b)))
+                        .collect_vec();
+
+                    changed |= kept.len() != before;
+                    domains[group] = kept;
+                }
+            }
+        }
+    }
+}
+
+/// Removes sections that can't be part of any schedule, without changing the order of the ones
+/// that can
+pub fn prune_conflicts(data: Vec<ClassesMapped>) -> Vec<ClassesMapped> {
+    let graph = ConflictGraph::new(&data);
+    let mut domains = data
+        .iter()
+        .map(|group| (0..group.len()).collect_vec())
+        .collect_vec();
+    graph.prune(&mut domains);
+
+    data.into_iter()
+        .zip(domains)
+        .map(|(group, domain)| {
+            group
+                .into_iter()
+                .enumerate()
+                .filter(|(idx, _)| domain.binary_search(idx).is_ok())
+                .map(|(_, class)| class)
+                .collect_vec()
+        })
+        .collect_vec()
+}
+
+/// Finds the same schedules as [`bruteforce_schedules`] in a different order
+///
+/// Sections are checked against a [`ConflictGraph`] up front. Picks the group with the fewest
+/// sections left first and drops every section that conflicts with a pick from the groups that
+/// are still open, backing out as soon as one of them runs dry. Classes are passed to `callback`
+/// in group order.
+pub fn propagate_schedules<'a, F: FnMut(&[Crn], &[&'a Schedule])>(
+    data: &'a [ClassesMapped],
+    callback: &mut F,
+) -> SearchStats {
+    let mut stats = SearchStats::default();
+    if data.is_empty() {
+        return stats;
+    }
+
+    let graph = ConflictGraph::new(data);
+    let mut domains = data
+        .iter()
+        .map(|group| (0..group.len()).collect_vec())
+        .collect_vec();
+    graph.prune(&mut domains);
+
+    if domains.iter().all(|domain| !domain.is_empty()) {
+        propagate(
+            data,
+            &graph,
+            &mut domains,
+            &mut vec![None; data.len()],
+            callback,
+            &mut stats,
+        );
+    }
+
+    stats
+}
+
+fn propagate<'a, F: FnMut(&[Crn], &[&'a Schedule])>(
+    data: &'a [ClassesMapped],
+    graph: &ConflictGraph,
+    domains: &mut [Vec<usize>],
+    assigned: &mut [Option<usize>],
+    callback: &mut F,
+    stats: &mut SearchStats,
+) {
+    // Most constrained group first
+    let group = (0..data.len())
+        .filter(|group| assigned[*group].is_none())
+        .min_by_key(|group| domains[*group].len());
+
+    let Some(group) = group else {
+        // Leaf
+        stats.solutions += 1;
+        let choices = data
+            .iter()
+            .zip(assigned.iter())
+            .map(|(options, choice)| &options[choice.expect("Every group is assigned")])
+            .collect_vec();
+        let classes = choices
+            .iter()
+            .flat_map(|choice| choice.crns())
+            .collect_vec();
+        let schedule = choices.iter().map(|choice| &choice.schedule).collect_vec();
+        (callback)(&classes, &schedule);
+        return;
+    };
+
+    let choices = std::mem::take(&mut domains[group]);
+    for choice in choices.iter().copied() {
+        stats.nodes += 1;
+        assigned[group] = Some(choice);
+
+        // Forward check every group that is still open
+        let mut saved = Vec::new();
+        let mut wiped_out = false;
+        for other in 0..data.len() {
+            if assigned[other].is_some() {
+                continue;
+            }
+
+            let kept = domains[other]
+                .iter()
+                .copied()
+                .filter(|it| !graph.conflicts(group, choice, other, *it))
+                .collect_vec();
+
+            if kept.len() != domains[other].len() {
+                wiped_out = kept.is_empty();
+                saved.push((other, std::mem::replace(&mut domains[other], kept)));
+                if wiped_out {
+                    break;
+                }
+            }
+        }
+
+        if !wiped_out {
+            propagate(data, graph, domains, assigned, callback, stats);
+        }
+
+        for (other, domain) in saved {
+            domains[other] = domain;
+        }
+        assigned[group] = None;
+    }
+    domains[group] = choices;
+}
+
+/// Lazily walks the same schedules as [`bruteforce_schedules`], in the same order
+///
+/// Every item is the chosen CRNs with their schedules. Stop whenever, and pick up later with
+/// [`Schedules::resume`] and the [`Schedules::path`] of the last schedule taken.
+#[derive(Clone, Debug)]
+pub struct Schedules<'a> {
+    data: &'a [ClassesMapped],
+    // Choice index in every group down to the current depth
+    path: Vec<usize>,
+    schedule: Vec<&'a Schedule>,
+    // Next choice index to try at the current depth
+    cursor: usize,
+    // The path is a full schedule that was just returned
+    yielded: bool,
+    done: bool,
+}
+
+impl<'a> Schedules<'a> {
+    pub fn new(data: &'a [ClassesMapped]) -> Self {
+        Self {
+            data,
+            path: Vec::with_capacity(data.len()),
+            schedule: Vec::with_capacity(data.len()),
+            cursor: 0,
+            yielded: false,
+            done: data.is_empty(),
+        }
+    }
+
+    /// Continues with the schedule after `path`, which has to come from [`Schedules::path`]
+    ///
+    /// `None` when the path isn't a schedule of `data` (ex: saved before the groups changed).
+    pub fn resume(data: &'a [ClassesMapped], path: &[usize]) -> Option<Self> {
+        if data.is_empty() || path.len() != data.len() {
+            return None;
+        }
+
+        let mut schedules = Self::new(data);
+        for (group, idx) in data.iter().zip(path) {
+            let schedule = &group.get(*idx)?.schedule;
+            if schedule.overlaps(&schedules.schedule) {
+                return None;
+            }
+
+            schedules.path.push(*idx);
+            schedules.schedule.push(schedule);
+        }
+        schedules.yielded = true;
+
+        Some(schedules)
+    }
+
+    /// Choice index in every group of the last schedule returned
+    pub fn path(&self) -> Option<&[usize]> {
+        if self.yielded {
+            Some(&self.path)
+        } else {
+            None
+        }
+    }
+
+    fn backtrack(&mut self) -> bool {
+        match self.path.pop() {
+            Some(idx) => {
+                self.schedule.pop();
+                self.cursor = idx + 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<'a> Iterator for Schedules<'a> {
+    type Item = (Vec<Crn>, Vec<&'a Schedule>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.yielded {
+            self.yielded = false;
+            self.backtrack();
+        }
+
+        while !self.done {
+            let group = &self.data[self.path.len()];
+            let choice = group[self.cursor.min(group.len())..]
+                .iter()
+                .position(|choice| !choice.schedule.overlaps(&self.schedule))
+                .map(|offset| self.cursor + offset);
+
+            match choice {
+                Some(idx) => {
+                    self.path.push(idx);
+                    self.schedule.push(&group[idx].schedule);
+                    self.cursor = 0;
+
+                    if self.path.len() == self.data.len() {
+                        self.yielded = true;
+
+                        let classes = self
+                            .data
+                            .iter()
+                            .zip(&self.path)
+                            .flat_map(|(group, idx)| group[*idx].crns())
+                            .collect();
+                        return Some((classes, self.schedule.clone()));
+                    }
+                }
+                None => {
+                    if !self.backtrack() {
+                        self.done = true;
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Finds the best `count` schedules, the same ones (in the same order) as scoring every schedule
+/// from [`bruteforce_schedules`] and sorting them, without visiting branches that can't beat them
+pub fn top_schedules(
+    data: &[ClassesMapped],
+    priorities: &Priorities,
+    count: usize,
+) -> Vec<((f64, Priorities), Vec<Crn>)> {
+    top_schedules_parallel(data, priorities, count, 1)
+}
+
+/// Same as [`top_schedules`], with the first one or two groups split between `threads` threads
+pub fn top_schedules_parallel(
+    data: &[ClassesMapped],
+    priorities: &Priorities,
+    count: usize,
+    threads: usize,
+) -> Vec<((f64, Priorities), Vec<Crn>)> {
+    let mut top = TopSchedules::new(count);
+    search_into(
+        data,
+        priorities,
+        &Travel::default(),
+        CreditBounds::ANY,
+        0,
+        &[],
+        &mut top,
+        threads,
+    );
+    top.into_sorted()
+}
+
+/// Best `count` schedules out of every variant within the credit bounds, earlier variants win
+/// ties
+///
+/// Meetings closer than the `travel` time between their places never go together.
+pub fn top_variant_schedules(
+    variants: &[Variant],
+    priorities: &Priorities,
+    travel: &TravelTimes,
+    credits: CreditBounds,
+    count: usize,
+    threads: usize,
+) -> Vec<((f64, Priorities), Vec<Crn>)> {
+    let travel = Travel::new(travel);
+    let mut top = TopSchedules::new(count);
+    for (idx, variant) in variants.iter().enumerate() {
+        search_into(
+            &variant.groups,
+            priorities,
+            &travel,
+            credits,
+            variant.optional,
+            &[idx],
+            &mut top,
+            threads,
+        );
+    }
+
+    top.into_sorted()
+}
+
+// Adds the best schedules of `data` to `top`, every path starts with `prefix`
+#[allow(clippy::too_many_arguments)]
+fn search_into(
+    data: &[ClassesMapped],
+    priorities: &Priorities,
+    travel: &Travel,
+    credits: CreditBounds,
+    optional: usize,
+    prefix: &[usize],
+    top: &mut TopSchedules,
+    threads: usize,
+) {
+    if top.count == 0 || data.is_empty() {
+        return;
+    }
+
+    let search = TopSearch {
+        rest: Envelope::suffixes(data),
+        rest_tally: Tally::suffixes(data),
+        priorities,
+        credits,
+        optional,
+        travel,
+    };
+
+    if threads <= 1 || data.len() <= 1 {
+        search.run(
+            data,
+            Tally::default(),
+            &mut prefix.to_vec(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+            top,
+        );
+        return;
+    }
+
+    // Enough starting points to keep every thread busy when some branches finish early
+    let depth = if data[0].len() >= threads * 4 || data.len() == 2 {
+        1
+    } else {
+        2
+    };
+
+    let mut prefixes = Vec::new();
+    split_prefixes(
+        &data[..depth],
+        travel,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut prefixes,
+    );
+
+    let next = AtomicUsize::new(0);
+    let found = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..threads.min(prefixes.len()) {
+            scope.spawn(|| {
+                let mut local = top.below();
+
+                // Prefixes are taken in order, so every thread still finds its schedules in the
+                // same order as the serial search
+                while let Some(split) = prefixes.get(next.fetch_add(1, AtomicOrdering::Relaxed)) {
+                    let mut path = prefix.to_vec();
+                    let mut classes = Vec::with_capacity(data.len());
+                    let mut schedule = Vec::with_capacity(data.len());
+                    let mut tally = Tally::default();
+                    for (group, idx) in data.iter().zip(split) {
+                        path.push(*idx);
+                        classes.extend(group[*idx].crns());
+                        schedule.push(&group[*idx].schedule);
+                        tally = tally.add(Tally::of(&group[*idx]));
+                    }
+
+                    if search.fits(tally, depth)
+                        && !search.hopeless(&schedule, tally, depth, &local)
+                    {
+                        search.run(
+                            &data[depth..],
+                            tally,
+                            &mut path,
+                            &mut classes,
+                            &mut schedule,
+                            &mut local,
+                        );
+                    }
+                }
+
+                found.lock().unwrap().push(local);
+            });
+        }
+    });
+
+    for local in found.into_inner().unwrap() {
+        top.merge(local);
+    }
+}
+
+// Every choice index path through `data` that doesn't conflict, in search order
+fn split_prefixes<'a>(
+    data: &'a [ClassesMapped],
+    travel: &Travel,
+    path: &mut Vec<usize>,
+    schedule: &mut Vec<&'a Schedule>,
+    prefixes: &mut Vec<Vec<usize>>,
+) {
+    for (idx, choice) in data.first().iter().flat_map(|it| it.iter()).enumerate() {
+        if travel.conflicts(&choice.schedule, schedule) {
+            continue;
+        }
+
+        path.push(idx);
+        schedule.push(&choice.schedule);
+
+        if data.len() <= 1 {
+            prefixes.push(path.clone());
+        } else {
+            split_prefixes(&data[1..], travel, path, schedule, prefixes);
+        }
+
+        path.pop();
+        schedule.pop();
+    }
+}
+
+struct TopSearch<'p> {
+    // Envelope of the groups from each depth on
+    rest: Vec<Envelope>,
+    // What the groups from each depth on can add
+    rest_tally: Vec<Tally>,
+    priorities: &'p Priorities,
+    credits: CreditBounds,
+    optional: usize,
+    travel: &'p Travel,
+}
+
+impl TopSearch<'_> {
+    // The groups that are left can still bring the credits within bounds
+    fn fits(&self, tally: Tally, depth: usize) -> bool {
+        self.credits
+            .allows(tally.add(self.rest_tally[depth]).credits)
+    }
+
+    // Least and most the picks can score, averaging free seats over every group of the search.
+    // Transfers aren't known until the schedule is
+    fn picks(&self, tally: Tally) -> (Picks, Picks) {
+        let groups = (self.rest.len() - 1) as f64;
+        let picks = |free_seats: u32, preferred: u32, tight_transfers: f64| Picks {
+            optional: self.optional,
+            free_seats: free_seats as f64 / groups,
+            preferred: preferred as usize,
+            tight_transfers,
+        };
+        let most_transfers = if self.travel.is_empty() {
+            0.0
+        } else {
+            f64::INFINITY
+        };
+
+        (
+            picks(tally.free_seats.0, tally.preferred.0, 0.0),
+            picks(tally.free_seats.1, tally.preferred.1, most_transfers),
+        )
+    }
+
+    // Ties go to the schedule found first, so anything that can only match the worst kept
+    // schedule would be thrown away anyway
+    fn hopeless(
+        &self,
+        schedule: &[&Schedule],
+        tally: Tally,
+        depth: usize,
+        top: &TopSchedules,
+    ) -> bool {
+        top.worst()
+            .map(|worst| {
+                let (low, high) = self.picks(tally.add(self.rest_tally[depth]));
+
+                self.priorities
+                    .upper_bound(schedule, &self.rest[depth], &low, &high)
+                    <= worst
+            })
+            .unwrap_or(false)
+    }
+
+    fn run<'a>(
+        &self,
+        data: &'a [ClassesMapped],
+        tally: Tally,
+        path: &mut Vec<usize>,
+        classes: &mut Vec<Crn>,
+        schedule: &mut Vec<&'a Schedule>,
+        top: &mut TopSchedules,
+    ) {
+        let depth = self.rest.len() - 1 - data.len();
+
+        for (idx, choice) in data.first().iter().flat_map(|it| it.iter()).enumerate() {
+            let tally = tally.add(Tally::of(choice));
+            if self.travel.conflicts(&choice.schedule, schedule) || !self.fits(tally, depth + 1) {
+                continue;
+            }
+
+            let len = classes.len();
+            path.push(idx);
+            classes.extend(choice.crns());
+            schedule.push(&choice.schedule);
+
+            if data.len() <= 1 {
+                // Leaf
+                let mut flat = Schedule::flatten(schedule);
+                flat.sort();
+
+                let picks = Picks {
+                    tight_transfers: self.travel.tight_transfers(&flat.data()),
+                    ..self.picks(tally).0
+                };
+                let (score, breakdown) = self.priorities.score_picks(&flat.data(), &picks);
+                top.push(score, breakdown, path, classes);
+            } else if !self.hopeless(schedule, tally, depth + 1, top) {
+                self.run(&data[1..], tally, path, classes, schedule, top);
+            }
+
+            path.pop();
+            classes.truncate(len);
+            schedule.pop();
+        }
+    }
+}
+
+// Totals of the sections picked so far, or the least and most the remaining groups can add
+#[derive(Copy, Clone, Debug, Default)]
+struct Tally {
+    credits: (u64, u64),
+    free_seats: (u32, u32),
+    preferred: (u32, u32),
+}
+
+impl Tally {
+    fn of(section: &SmallClass) -> Self {
+        Self {
+            credits: section.credits,
+            free_seats: (section.free_seats, section.free_seats),
+            preferred: (section.preferred, section.preferred),
+        }
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            credits: (
+                self.credits.0 + other.credits.0,
+                self.credits.1 + other.credits.1,
+            ),
+            free_seats: (
+                self.free_seats.0 + other.free_seats.0,
+                self.free_seats.1 + other.free_seats.1,
+            ),
+            preferred: (
+                self.preferred.0 + other.preferred.0,
+                self.preferred.1 + other.preferred.1,
+            ),
+        }
+    }
+
+    // Every group from each index to the end, with nothing at the end
+    fn suffixes(data: &[ClassesMapped]) -> Vec<Self> {
+        let mut suffixes = vec![Self::default(); data.len() + 1];
+        for (idx, group) in data.iter().enumerate().rev() {
+            let group = Self {
+                credits: (
+                    group.iter().map(|it| it.credits.0).min().unwrap_or(0),
+                    group.iter().map(|it| it.credits.1).max().unwrap_or(0),
+                ),
+                free_seats: (
+                    group.iter().map(|it| it.free_seats).min().unwrap_or(0),
+                    group.iter().map(|it| it.free_seats).max().unwrap_or(0),
+                ),
+                preferred: (
+                    group.iter().map(|it| it.preferred).min().unwrap_or(0),
+                    group.iter().map(|it| it.preferred).max().unwrap_or(0),
+                ),
+            };
+            suffixes[idx] = group.add(suffixes[idx + 1]);
+        }
+
+        suffixes
+    }
+}
+
+/// Bounded heap of the best schedules seen so far
+pub struct TopSchedules {
+    count: usize,
+    heap: BinaryHeap<Ranked>,
+    // Score to beat that was set by schedules kept somewhere else
+    floor: Option<f64>,
+}
+
+// Ordered worst first so the heap can drop its worst schedule, equal scores are broken by the
+// order the search found them in (the choice index in every group)
+struct Ranked {
+    score: f64,
+    breakdown: Priorities,
+    path: Vec<usize>,
+    classes: Vec<Crn>,
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(&self.score, &other.score)
+            .reverse()
+            .then_with(|| self.path.cmp(&other.path))
+    }
+}
+
+impl TopSchedules {
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            heap: BinaryHeap::with_capacity(count + 1),
+            floor: None,
+        }
+    }
+
+    /// Empty heap that only keeps schedules that would make it into this one, for schedules
+    /// found after everything in this one
+    pub fn below(&self) -> Self {
+        Self {
+            floor: self.worst(),
+            ..Self::new(self.count)
+        }
+    }
+
+    /// Score a schedule has to beat to be kept, `None` until the heap is full
+    pub fn worst(&self) -> Option<f64> {
+        let full = if self.heap.len() < self.count {
+            None
+        } else {
+            self.heap.peek().map(|it| it.score)
+        };
+
+        match (full, self.floor) {
+            (Some(worst), Some(floor)) => Some(worst.max(floor)),
+            (worst, floor) => worst.or(floor),
+        }
+    }
+
+    /// Schedules have to be pushed in the order they are found, equal scores keep the first one
+    pub fn push(&mut self, score: f64, breakdown: Priorities, path: &[usize], classes: &[Crn]) {
+        if let Some(worst) = self.worst() {
+            if score <= worst {
+                return;
+            }
+        }
+
+        self.insert(Ranked {
+            score,
+            breakdown,
+            path: path.to_vec(),
+            classes: classes.to_vec(),
+        });
+    }
+
+    /// Keeps the best of both, as if every schedule had been pushed into one of them
+    pub fn merge(&mut self, other: Self) {
+        for ranked in other.heap {
+            self.insert(ranked);
+        }
+    }
+
+    fn insert(&mut self, ranked: Ranked) {
+        if self.count == 0 {
+            return;
+        }
+
+        self.heap.push(ranked);
+        if self.heap.len() > self.count {
+            self.heap.pop();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Best schedule first
+    pub fn into_sorted(self) -> Vec<((f64, Priorities), Vec<Crn>)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|it| ((it.score, it.breakdown), it.classes))
+            .collect()
+    }
+}
+
+// What a single day could look like once the remaining groups are picked
+#[derive(Copy, Clone, Debug)]
+struct DayRange {
+    // Some pick is guaranteed to meet on this day
+    must: bool,
+    // Some pick could meet on this day
+    may: bool,
+    min_start: u16,
+    max_first_start: u16,
+    min_last_end: u16,
+    max_end: u16,
+    min_span: u16,
+}
+
+impl DayRange {
+    const EMPTY: Self = Self {
+        must: false,
+        may: false,
+        min_start: u16::MAX,
+        max_first_start: 0,
+        min_last_end: u16::MAX,
+        max_end: 0,
+        min_span: u16::MAX,
+    };
+
+    fn of(blocks: &[Block]) -> Self {
+        match day_extent(blocks.iter()) {
+            Some((start, end)) => Self {
+                must: true,
+                may: true,
+                min_start: start,
+                max_first_start: start,
+                min_last_end: end,
+                max_end: end,
+                min_span: end - start,
+            },
+            None => Self::EMPTY,
+        }
+    }
+
+    // Exactly one of the two is picked
+    fn either(self, other: Self) -> Self {
+        Self {
+            must: self.must && other.must,
+            ..self.both(other)
+        }
+    }
+
+    // Both of them are picked
+    fn both(self, other: Self) -> Self {
+        Self {
+            must: self.must || other.must,
+            may: self.may || other.may,
+            min_start: self.min_start.min(other.min_start),
+            max_first_start: self.max_first_start.max(other.max_first_start),
+            min_last_end: self.min_last_end.min(other.min_last_end),
+            max_end: self.max_end.max(other.max_end),
+            min_span: self.min_span.min(other.min_span),
+        }
+    }
+}
+
+/// Everything the remaining groups of a search could add to a schedule, per day
+#[derive(Copy, Clone, Debug)]
+pub struct Envelope([DayRange; 7]);
+
+impl Envelope {
+    const NONE: Self = Self([DayRange::EMPTY; 7]);
+
+    fn of(schedule: &Schedule) -> Self {
+        let data = schedule.data();
+        Self(std::array::from_fn(|day| DayRange::of(data[day])))
+    }
+
+    fn group(group: &[SmallClass]) -> Self {
+        group
+            .iter()
+            .map(|class| Envelope::of(&class.schedule))
+            .reduce(|a, b| Self(std::array::from_fn(|day| a.0[day].either(b.0[day]))))
+            .unwrap_or(Self::NONE)
+    }
+
+    /// Envelope of every group from each index to the end, with an empty one at the end
+    pub fn suffixes(data: &[ClassesMapped]) -> Vec<Envelope> {
+        let mut suffixes = vec![Self::NONE; data.len() + 1];
+        for (idx, group) in data.iter().enumerate().rev() {
+            let group = Self::group(group);
+            let after = suffixes[idx + 1];
+            suffixes[idx] = Self(std::array::from_fn(|day| group.0[day].both(after.0[day])));
+        }
+
+        suffixes
+    }
+}
+
+// First start and last end of a day
+fn day_extent<'a>(blocks: impl Iterator<Item = &'a Block>) -> Option<(u16, u16)> {
+    blocks.fold(None, |extent, block| {
+        let (start, end) = (block.0, block.0 + block.1);
+        Some(match extent {
+            Some((first, last)) => (u16::min(first, start), u16::max(last, end)),
+            None => (start, end),
+        })
+    })
+}
+
 pub fn unmap_classes<'a>(bank: &'a ClassBank, classes: &[Crn]) -> Vec<&'a Class> {
     classes
-        .into_iter()
+        .iter()
         .map(|crn| bank.get(crn).expect("Got bad crn"))
         .collect_vec()
 }
@@ -134,15 +1150,16 @@ pub fn find_alts<'a>(
     classes: &Vec<&'a Class>,
 ) -> Vec<(&'a Class, Vec<&'a Class>)> {
     classes
-        .into_iter()
+        .iter()
         .map(|class| {
             (
                 &**class,
-                bank.into_iter()
+                bank.iter()
                     .filter(|(include, _)| include.matches(class))
-                    .flat_map(|(_, classes)| classes.into_iter())
+                    .flat_map(|(_, classes)| classes.iter())
                     .filter(|it| {
-                        it.subject_course == class.subject_course && it.schedule == class.schedule
+                        it.subject_course == class.subject_course
+                            && it.schedule.same_times(&class.schedule)
                     })
                     .map(|it| &**it)
                     .collect(),
@@ -153,25 +1170,65 @@ pub fn find_alts<'a>(
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub enum Constraint {
-    BlockTimes { start: Time, end: Time, days: Days },
-    BlockDays { days: Days },
-    StartAfter { time: Time, days: Days },
-    EndBefore { time: Time, days: Days },
-    Campus { name: String },
+    BlockTimes {
+        start: Time,
+        end: Time,
+        days: Days,
+    },
+    BlockDays {
+        days: Days,
+    },
+    StartAfter {
+        time: Time,
+        days: Days,
+    },
+    EndBefore {
+        time: Time,
+        days: Days,
+    },
+    Campus {
+        name: String,
+    },
+    /// Credit hours of the whole schedule, variable credit sections count anywhere in their range
+    Credits {
+        min: u64,
+        max: u64,
+    },
+    /// Sections that are open with seats left
+    Open,
+    /// Open sections, or full ones with room on a wait list that has at most `max_ahead` students
+    WaitList {
+        max_ahead: u64,
+    },
+    /// Cross listed sections with seats left in the whole cross list
+    CrossListSeats,
+    /// Sections of `course`, or of every course, taught by one of `include` (when there are any)
+    /// and none of `exclude`, matching any part of the instructor's name
+    Instructor {
+        course: Option<String>,
+        #[serde(default)]
+        include: Vec<String>,
+        #[serde(default)]
+        exclude: Vec<String>,
+    },
+    /// Sections taught in one of the listed ways, see [`Class::delivery`]
+    InstructionalMethod {
+        allow: Vec<Delivery>,
+    },
 }
 
 impl Constraint {
     pub fn allows(&self, class: &Class) -> bool {
         match self {
             Constraint::BlockDays { days } => {
-                for meeting in &class.meetings {
+                for meeting in class.meetings.iter().filter(|it| !it.excluded) {
                     if meeting.days & *days != Days::never() {
                         return false;
                     }
                 }
             }
             Constraint::StartAfter { time, days } => {
-                for meeting in &class.meetings {
+                for meeting in class.meetings.iter().filter(|it| !it.excluded) {
                     if meeting.days & *days != Days::never() {
                         if let Some(ref start_time) = meeting.start_time {
                             if start_time < time {
@@ -184,7 +1241,7 @@ impl Constraint {
                 }
             }
             Constraint::EndBefore { time, days } => {
-                for meeting in &class.meetings {
+                for meeting in class.meetings.iter().filter(|it| !it.excluded) {
                     if meeting.days & *days != Days::never() {
                         if let Some(ref end_time) = meeting.end_time {
                             if end_time > time {
@@ -197,7 +1254,7 @@ impl Constraint {
                 }
             }
             Constraint::BlockTimes { start, end, days } => {
-                for meeting in &class.meetings {
+                for meeting in class.meetings.iter().filter(|it| !it.excluded) {
                     if meeting.days & *days != Days::never() {
                         if let Some((ref start_time, ref end_time)) =
                             meeting.start_time.zip(meeting.end_time)
@@ -221,13 +1278,135 @@ impl Constraint {
                     return false;
                 }
             }
+            // Only classes that are too big on their own, the rest is up to the search
+            Constraint::Credits { max, .. } => {
+                if class.credit_hours.range().0 > *max {
+                    return false;
+                }
+            }
+            Constraint::Open => {
+                if !class.has_seats() {
+                    return false;
+                }
+            }
+            Constraint::WaitList { max_ahead } => {
+                if !class.has_seats()
+                    && (class.wait_list.available <= 0 || class.wait_list.count > *max_ahead)
+                {
+                    return false;
+                }
+            }
+            Constraint::CrossListSeats => {
+                if let Some(cross_list) = &class.cross_list {
+                    if cross_list.cross_list_available <= 0 {
+                        return false;
+                    }
+                }
+            }
+            Constraint::Instructor {
+                course,
+                include,
+                exclude,
+            } => {
+                if course.as_ref().map(|it| it == &class.subject_course) == Some(false) {
+                    return true;
+                }
+
+                if !include.is_empty() && !include.iter().any(|name| class.taught_by(name)) {
+                    return false;
+                }
+                if exclude.iter().any(|name| class.taught_by(name)) {
+                    return false;
+                }
+            }
+            Constraint::InstructionalMethod { allow } => {
+                if !allow.contains(&class.delivery()) {
+                    return false;
+                }
+            }
         }
 
         true
     }
 }
 
+impl Display for Constraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constraint::BlockTimes { start, end, days } => {
+                write!(f, "blocked {start}-{end} on {days}")
+            }
+            Constraint::BlockDays { days } => write!(f, "blocked days {days}"),
+            Constraint::StartAfter { time, days } => write!(f, "start after {time} on {days}"),
+            Constraint::EndBefore { time, days } => write!(f, "end before {time} on {days}"),
+            Constraint::Campus { name } => write!(f, "campus {name}"),
+            Constraint::Credits { min, max } => write!(f, "{min}-{max} credits"),
+            Constraint::Open => write!(f, "open sections"),
+            Constraint::WaitList { max_ahead } => {
+                write!(f, "wait lists with at most {max_ahead} ahead")
+            }
+            Constraint::CrossListSeats => write!(f, "cross list seats left"),
+            Constraint::Instructor {
+                course,
+                include,
+                exclude,
+            } => {
+                if let Some(course) = course {
+                    write!(f, "{course} ")?;
+                }
+                if !include.is_empty() {
+                    write!(f, "taught by {}", include.join(" or "))?;
+                }
+                if !include.is_empty() && !exclude.is_empty() {
+                    write!(f, ", ")?;
+                }
+                if !exclude.is_empty() {
+                    write!(f, "not taught by {}", exclude.join(" or "))?;
+                }
+
+                Ok(())
+            }
+            Constraint::InstructionalMethod { allow } => {
+                write!(f, "{} sections", allow.iter().join(" or "))
+            }
+        }
+    }
+}
+
+/// Fewest and most credit hours a schedule can add up to
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CreditBounds {
+    pub min: u64,
+    pub max: u64,
+}
+
+impl CreditBounds {
+    pub const ANY: Self = Self {
+        min: 0,
+        max: u64::MAX,
+    };
+
+    /// Every [`Constraint::Credits`] at once
+    pub fn of(constraints: &[Constraint]) -> Self {
+        constraints
+            .iter()
+            .fold(Self::ANY, |bounds, constraint| match constraint {
+                Constraint::Credits { min, max } => Self {
+                    min: bounds.min.max(*min),
+                    max: bounds.max.min(*max),
+                },
+                _ => bounds,
+            })
+    }
+
+    /// Some way of taking `(low, high)` credits lands within the bounds
+    pub fn allows(&self, credits: (u64, u64)) -> bool {
+        credits.0 <= self.max && credits.1 >= self.min
+    }
+}
+
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Priorities {
     pub similar_start_time: f64,
     pub similar_end_time: f64,
@@ -235,10 +1414,54 @@ pub struct Priorities {
     pub free_block: f64,
     pub free_day: f64,
     pub day_length: f64,
+    /// Optional courses taken, see [`Include::Optional`]
+    pub optional_course: f64,
+    /// Average percent of seats still free in the sections
+    pub free_seats: f64,
+    /// Sections taught by one of the [`crate::plan::Plan::instructors`]
+    pub preferred_instructor: f64,
+    /// Minutes short of twice the [`crate::plan::Plan::travel`] time between back to back
+    /// meetings in different places
+    pub tight_transfers: f64,
+}
+
+/// An instructor to prefer, for one course or for every course
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct PreferredInstructor {
+    pub course: Option<String>,
+    pub name: String,
+}
+
+impl PreferredInstructor {
+    pub fn matches(&self, class: &Class) -> bool {
+        self.course
+            .as_ref()
+            .map(|course| course == &class.subject_course)
+            .unwrap_or(true)
+            && class.taught_by(&self.name)
+    }
+}
+
+/// What a schedule scores for the sections it takes rather than their times
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Picks {
+    /// Optional courses taken, see [`Include::Optional`]
+    pub optional: usize,
+    /// Average percent of seats still free
+    pub free_seats: f64,
+    /// Sections taught by one of [`crate::plan::Plan::instructors`]
+    pub preferred: usize,
+    /// See [`Travel::tight_transfers`]
+    pub tight_transfers: f64,
 }
 
 impl Priorities {
-    pub fn score(&self, schedule: &[&[(u16, u16)]; 7]) -> (f64, Priorities) {
+    /// Score of the times alone, as if nothing was picked that [`Picks`] counts
+    pub fn score(&self, schedule: &[&[Block]; 7]) -> (f64, Priorities) {
+        self.score_picks(schedule, &Picks::default())
+    }
+
+    pub fn score_picks(&self, schedule: &[&[Block]; 7], picks: &Picks) -> (f64, Priorities) {
         let mut start_time_avg = 0;
         let mut end_time_total = 0;
         let mut free_blocks_total = 0;
@@ -248,10 +1471,7 @@ impl Priorities {
         let mut end_times = [None; 7];
 
         for (idx, day) in schedule.iter().enumerate() {
-            if let Some((first, last)) = Option::zip(day.first(), day.last()) {
-                let start_time = first.0;
-                let end_time = last.0 + last.1;
-
+            if let Some((start_time, end_time)) = day_extent(day.iter()) {
                 start_time_avg += start_time;
                 end_time_total += end_time;
                 days += 1;
@@ -261,7 +1481,8 @@ impl Priorities {
 
                 let mut free_block = 0;
                 for (class_a, class_b) in day.iter().tuple_windows() {
-                    let time_between = class_b.0 - (class_a.0 + class_a.1);
+                    // Blocks on disjoint dates can share a time slot
+                    let time_between = class_b.0.saturating_sub(class_a.0 + class_a.1);
 
                     free_block = free_block.max(time_between);
 
@@ -305,22 +1526,169 @@ impl Priorities {
 
         let free_days = 5 - days as i32;
 
-        (
-            (0.0 - similar_start_time as f64 * self.similar_start_time
-                - similar_end_time as f64 * self.similar_end_time
-                + time_between as f64 * self.time_between_classes
-                + free_blocks as f64 * self.free_block
-                + free_days as f64 * self.free_day
-                - day_length as f64 * self.day_length),
-            Priorities {
-                similar_start_time: -(similar_start_time as f64),
-                similar_end_time: -(similar_end_time as f64),
-                time_between_classes: time_between as f64,
-                free_block: free_blocks as f64,
-                free_day: free_days as f64,
-                day_length: -(day_length as f64),
+        let breakdown = Priorities {
+            similar_start_time: -(similar_start_time as f64),
+            similar_end_time: -(similar_end_time as f64),
+            time_between_classes: time_between as f64,
+            free_block: free_blocks as f64,
+            free_day: free_days as f64,
+            day_length: -(day_length as f64),
+            optional_course: picks.optional as f64,
+            free_seats: picks.free_seats,
+            preferred_instructor: picks.preferred as f64,
+            tight_transfers: -picks.tight_transfers,
+        };
+
+        (self.weigh(&breakdown), breakdown)
+    }
+
+    /// Every weighted part of the score by name, in the order they're weighed
+    pub fn terms(&self) -> [(&'static str, f64); 10] {
+        [
+            ("similar_start_time", self.similar_start_time),
+            ("similar_end_time", self.similar_end_time),
+            ("time_between_classes", self.time_between_classes),
+            ("free_block", self.free_block),
+            ("free_day", self.free_day),
+            ("day_length", self.day_length),
+            ("optional_course", self.optional_course),
+            ("free_seats", self.free_seats),
+            ("preferred_instructor", self.preferred_instructor),
+            ("tight_transfers", self.tight_transfers),
+        ]
+    }
+
+    fn weigh(&self, breakdown: &Priorities) -> f64 {
+        self.terms()
+            .into_iter()
+            .zip(breakdown.terms())
+            .map(|((_, weight), (_, value))| weight * value)
+            .sum()
+    }
+
+    /// Highest score any schedule made of `partial` and a pick from each remaining group could get,
+    /// with picks anywhere between `low` and `high`
+    pub fn upper_bound(
+        &self,
+        partial: &[&Schedule],
+        rest: &Envelope,
+        low: &Picks,
+        high: &Picks,
+    ) -> f64 {
+        // (low, high) of every part of the score that isn't weighted yet
+        let mut must_days = 0;
+        let mut may_days = 0;
+        let (mut start_min, mut start_max) = (u16::MAX, 0);
+        let (mut end_min, mut end_max) = (u16::MAX, 0);
+        let (mut must_start_low, mut must_start_high) = (0, u16::MAX);
+        let (mut must_end_low, mut must_end_high) = (0, u16::MAX);
+        let (mut span_min, mut span_max) = (u16::MAX, 0);
+
+        for (day, rest) in rest.0.iter().enumerate() {
+            let extent = day_extent(partial.iter().flat_map(|it| it.data[day].iter()));
+
+            let (must, start_low, start_high, end_low, end_high, span_low) = match extent {
+                Some((start, end)) => (
+                    true,
+                    start.min(rest.min_start),
+                    start,
+                    end,
+                    end.max(rest.max_end),
+                    end - start,
+                ),
+                None if rest.may => (
+                    rest.must,
+                    rest.min_start,
+                    rest.max_first_start,
+                    rest.min_last_end,
+                    rest.max_end,
+                    rest.min_span,
+                ),
+                None => continue,
+            };
+
+            may_days += 1;
+            start_min = start_min.min(start_low);
+            start_max = start_max.max(start_high);
+            end_min = end_min.min(end_low);
+            end_max = end_max.max(end_high);
+            span_min = span_min.min(span_low);
+            span_max = span_max.max(end_high.saturating_sub(start_low));
+
+            if must {
+                must_days += 1;
+                must_start_low = must_start_low.max(start_low);
+                must_start_high = must_start_high.min(start_high);
+                must_end_low = must_end_low.max(end_low);
+                must_end_high = must_end_high.min(end_high);
+            }
+        }
+
+        let pick = |(low, high): (f64, f64), weight: f64| if weight < 0.0 { low } else { high };
+        let picks = Picks {
+            optional: if self.optional_course < 0.0 {
+                low.optional
+            } else {
+                high.optional
             },
-        )
+            free_seats: pick((low.free_seats, high.free_seats), self.free_seats),
+            preferred: if self.preferred_instructor < 0.0 {
+                low.preferred
+            } else {
+                high.preferred
+            },
+            // Counted against the score
+            tight_transfers: if self.tight_transfers < 0.0 {
+                high.tight_transfers
+            } else {
+                low.tight_transfers
+            },
+        };
+
+        if may_days == 0 {
+            return self.score_picks(&[&[]; 7], &picks).0;
+        }
+
+        // Two days that are this far apart can't both be close to the average
+        let spread = |low: u16, high: u16| {
+            let gap = low.saturating_sub(high) as i32;
+            (gap * gap / 2 / may_days) as f64
+        };
+        let variance = |min: u16, max: u16| {
+            let range = max.saturating_sub(min) as i32;
+            (range * range) as f64
+        };
+
+        let similar_start_time = (
+            spread(must_start_low, must_start_high),
+            variance(start_min, start_max),
+        );
+        let similar_end_time = (
+            spread(must_end_low, must_end_high),
+            variance(end_min, end_max),
+        );
+        let gaps = (0.0, span_max as f64);
+        let free_day = ((5 - may_days) as f64, (5 - must_days) as f64);
+        let day_length = if must_days == 0 {
+            (0.0, span_max as f64)
+        } else {
+            (span_min as f64, span_max as f64)
+        };
+
+        let negate = |(low, high): (f64, f64)| (-high, -low);
+
+        self.weigh(&Priorities {
+            similar_start_time: pick(negate(similar_start_time), self.similar_start_time),
+            similar_end_time: pick(negate(similar_end_time), self.similar_end_time),
+            time_between_classes: pick(gaps, self.time_between_classes),
+            free_block: pick(gaps, self.free_block),
+            free_day: pick(free_day, self.free_day),
+            day_length: pick(negate(day_length), self.day_length),
+            optional_course: picks.optional as f64,
+            free_seats: picks.free_seats,
+            preferred_instructor: picks.preferred as f64,
+            tight_transfers: -picks.tight_transfers,
+        })
     }
 }
 
@@ -334,6 +1702,15 @@ pub enum Include {
         course_type: Option<String>,
     },
     All,
+    /// Exactly `count` of the options
+    AnyOf {
+        options: Vec<Include>,
+        count: usize,
+    },
+    /// Taken when it fits, rewarded by [`Priorities::optional_course`]
+    Optional {
+        include: Box<Include>,
+    },
 }
 
 impl Include {
@@ -360,8 +1737,89 @@ impl Include {
                 }
             }
             Include::All => return true,
+            Include::AnyOf { options, .. } => {
+                return options.iter().any(|option| option.matches(class))
+            }
+            Include::Optional { include } => return include.matches(class),
         }
 
         false
     }
+
+    /// The includes that classes are grouped by, everything but [`Include::AnyOf`] and
+    /// [`Include::Optional`]
+    pub fn leaves(&self) -> Vec<&Include> {
+        match self {
+            Include::AnyOf { options, .. } => {
+                options.iter().flat_map(|option| option.leaves()).collect()
+            }
+            Include::Optional { include } => include.leaves(),
+            _ => vec![self],
+        }
+    }
+
+    /// Every way of picking leaves from the includes, with how many optional courses each takes
+    ///
+    /// Picks that take an optional course come before the ones that skip it.
+    pub fn selections(includes: &[Include]) -> Vec<(Vec<&Include>, usize)> {
+        Include::combine(includes.iter().map(|include| include.picks()))
+    }
+
+    // Every pick of the first, with every pick of the second, ...
+    fn combine<'a>(
+        picks: impl Iterator<Item = Vec<(Vec<&'a Include>, usize)>>,
+    ) -> Vec<(Vec<&'a Include>, usize)> {
+        picks.fold(vec![(Vec::new(), 0)], |selections, picks| {
+            selections
+                .iter()
+                .flat_map(|(leaves, optional)| {
+                    picks.iter().map(move |(more, more_optional)| {
+                        let mut leaves = leaves.clone();
+                        leaves.extend(more);
+                        (leaves, optional + more_optional)
+                    })
+                })
+                .collect()
+        })
+    }
+
+    fn picks(&self) -> Vec<(Vec<&Include>, usize)> {
+        match self {
+            Include::AnyOf { options, count } => options
+                .iter()
+                .combinations(*count)
+                .flat_map(|chosen| {
+                    Include::combine(chosen.into_iter().map(|option| option.picks()))
+                })
+                .collect(),
+            Include::Optional { include } => {
+                let mut picks = include
+                    .picks()
+                    .into_iter()
+                    .map(|(leaves, optional)| (leaves, optional + 1))
+                    .collect_vec();
+                picks.push((Vec::new(), 0));
+                picks
+            }
+            _ => vec![(vec![self], 0)],
+        }
+    }
+}
+
+impl Display for Include {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Include::Class { crn } => write!(f, "CRN {crn}"),
+            Include::Course {
+                subject,
+                course_type: Some(course_type),
+            } => write!(f, "{subject} ({course_type})"),
+            Include::Course { subject, .. } => write!(f, "{subject}"),
+            Include::All => write!(f, "all classes"),
+            Include::AnyOf { options, count } => {
+                write!(f, "{count} of ({})", options.iter().join(", "))
+            }
+            Include::Optional { include } => write!(f, "optional {include}"),
+        }
+    }
 }
@@ -0,0 +1,85 @@
+use crate::ClassBank;
+use anyhow::{bail, Context};
+use clap::Args;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const RAW_DATA_FILE: &str = "raw_data.json";
+pub const DATA_FILE: &str = "data.json";
+
+// Selects the folder a binary reads and writes its term data from
+#[derive(Args, Clone, Debug, Default)]
+pub struct TermArgs {
+    /// Term code, resolved to a folder of the same name under the data root (ex: fall2023)
+    #[clap(long, env = "SCHEDUAL_TERM")]
+    pub term: Option<String>,
+    /// Folder holding the term data, takes precedence over `--term`
+    #[clap(long, env = "SCHEDUAL_DATA_DIR")]
+    pub data_dir: Option<PathBuf>,
+    /// Folder that term codes are resolved against
+    #[clap(long, env = "SCHEDUAL_DATA_ROOT", default_value = ".")]
+    pub data_root: PathBuf,
+}
+
+impl TermArgs {
+    pub fn resolve(&self) -> anyhow::Result<TermFolder> {
+        if let Some(data_dir) = &self.data_dir {
+            return Ok(TermFolder::new(data_dir));
+        }
+
+        if let Some(term) = &self.term {
+            return Ok(TermFolder::new(self.data_root.join(term)));
+        }
+
+        bail!(
+            "No term selected, use `--term`, `--data-dir`, `SCHEDUAL_TERM` or `SCHEDUAL_DATA_DIR`"
+        )
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TermFolder {
+    path: PathBuf,
+}
+
+impl TermFolder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn raw_data(&self) -> PathBuf {
+        self.path.join(RAW_DATA_FILE)
+    }
+
+    pub fn data(&self) -> PathBuf {
+        self.path.join(DATA_FILE)
+    }
+
+    pub fn create(&self) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.path)
+            .with_context(|| format!("Could not create {}", self.path.display()))
+    }
+
+    pub fn load_bank(&self) -> anyhow::Result<ClassBank> {
+        load_bank(self.data())
+    }
+}
+
+pub fn save_bank(path: impl AsRef<Path>, bank: &ClassBank) -> anyhow::Result<()> {
+    let path = path.as_ref();
+
+    let data = serde_json::to_string_pretty(bank).context("Could not serialize class bank")?;
+    fs::write(path, data).with_context(|| format!("Could not write {}", path.display()))
+}
+
+pub fn load_bank(path: impl AsRef<Path>) -> anyhow::Result<ClassBank> {
+    let path = path.as_ref();
+
+    let data =
+        fs::read_to_string(path).with_context(|| format!("Could not read {}", path.display()))?;
+    serde_json::from_str(&data).with_context(|| format!("Could not parse {}", path.display()))
+}
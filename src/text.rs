@@ -0,0 +1,210 @@
+use crate::timetable::{Timetable, TimetableBlock};
+use crate::{Class, Day, Days, Time};
+use anyhow::ensure;
+use chrono::NaiveDate;
+use cli_table::{ColorChoice, Table};
+use itertools::Itertools;
+use std::fmt::{Display, Formatter, Write};
+use std::str::FromStr;
+
+/// Minutes each row of a text timetable covers, one of 5, 10, 15 or 30
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Resolution(u8);
+
+impl Resolution {
+    pub const ALL: [u8; 4] = [5, 10, 15, 30];
+
+    pub fn new(minutes: u8) -> anyhow::Result<Self> {
+        ensure!(
+            Self::ALL.contains(&minutes),
+            "Bad resolution `{minutes}`, expected one of 5, 10, 15 or 30 minutes"
+        );
+        Ok(Self(minutes))
+    }
+
+    pub fn minutes(self) -> u8 {
+        self.0
+    }
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Self(30)
+    }
+}
+
+impl Display for Resolution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Resolution {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.trim().trim_end_matches("min").parse()?)
+    }
+}
+
+/// A weekly grid of `classes`, each paired with its alternates (see
+/// [`crate::solver::find_alts`]), with a row every `resolution` minutes
+///
+/// A meeting fills every row it overlaps, starting with its course and CRNs then its exact times.
+/// Meetings sharing a row get their own column of the day, with their dates when they don't run the
+/// whole term. Classes with meetings that have no set time are listed below the grid.
+pub fn timetable(classes: &[(&Class, Vec<&Class>)], resolution: Resolution) -> String {
+    let mut timetable = Timetable::new(&classes.iter().map(|(class, _)| *class).collect_vec());
+    let mut out = String::new();
+
+    let step = resolution.minutes() as u16;
+    // Two meetings can't share a cell
+    timetable.round_lanes(step);
+    // From the row holding the first start to the one holding the last end
+    let first = timetable.blocks.iter().map(|it| it.start.minutes()).min();
+    let last = timetable.blocks.iter().map(|it| it.end.minutes()).max();
+
+    if let Some((first, last)) = first.zip(last) {
+        let columns = columns(&timetable);
+        let rows = (first / step * step..last).step_by(step as usize);
+        let mut grid = rows
+            .map(|start| {
+                let mut row = vec![String::new(); columns.len() + 1];
+                row[0] = format!("{:#}", Time::new((start / 60) as u8, (start % 60) as u8));
+                (start, row)
+            })
+            .collect_vec();
+
+        let term = term_dates(&timetable);
+        for block in &timetable.blocks {
+            let column = 1 + columns
+                .iter()
+                .position(|it| *it == (block.day, block.lane))
+                .unwrap();
+            let lines = lines(block, classes, term);
+            let covered = grid.iter_mut().filter(|(start, _)| {
+                *start + step > block.start.minutes() && *start < block.end.minutes()
+            });
+
+            for (idx, (_, row)) in covered.enumerate() {
+                row[column] = lines.get(idx).cloned().unwrap_or_else(|| "·".to_owned());
+            }
+        }
+
+        let title = std::iter::once("Time".to_owned()).chain(columns.iter().map(|(day, lane)| {
+            if *lane == 0 {
+                format!("{day:?}")
+            } else {
+                format!("{day:?} {}", lane + 1)
+            }
+        }));
+        let display = grid
+            .into_iter()
+            .map(|(_, row)| row)
+            .table()
+            .title(title)
+            .color_choice(ColorChoice::Never)
+            .display()
+            .unwrap();
+        writeln!(out, "{display}").unwrap();
+    } else if timetable.untimed.is_empty() {
+        writeln!(out, "No classes").unwrap();
+    }
+
+    for class in &timetable.untimed {
+        let meetings = class
+            .meetings
+            .iter()
+            .filter(|it| !it.excluded && it.times().is_none())
+            .map(|it| it.meeting_type.as_str())
+            .join(", ");
+        writeln!(
+            out,
+            "No set time: {} {}, {} {meetings}",
+            class.subject_course,
+            crns(class, classes),
+            class.instructional_method
+        )
+        .unwrap();
+    }
+
+    out
+}
+
+// Monday through Friday and any weekend day with meetings, a column for each lane of the day
+fn columns(timetable: &Timetable) -> Vec<(Day, usize)> {
+    Days::everyday()
+        .iter()
+        .flat_map(|day| {
+            let lanes = timetable
+                .blocks
+                .iter()
+                .filter(|it| it.day == day)
+                .map(|it| it.lanes)
+                .max();
+            let weekend = matches!(day, Day::Saturday | Day::Sunday);
+            let lanes = lanes.unwrap_or(if weekend { 0 } else { 1 });
+            (0..lanes).map(move |lane| (day, lane))
+        })
+        .collect()
+}
+
+// First and last date anything with a set time meets on
+fn term_dates(timetable: &Timetable) -> (Option<NaiveDate>, Option<NaiveDate>) {
+    let start = timetable
+        .blocks
+        .iter()
+        .filter_map(|it| it.meeting.start_date)
+        .min();
+    let end = timetable
+        .blocks
+        .iter()
+        .filter_map(|it| it.meeting.end_date)
+        .max();
+    (start, end)
+}
+
+// Course and CRNs, exact times, then the dates for meetings that don't run the whole term
+fn lines(
+    block: &TimetableBlock,
+    classes: &[(&Class, Vec<&Class>)],
+    term: (Option<NaiveDate>, Option<NaiveDate>),
+) -> Vec<String> {
+    let mut lines = vec![
+        format!(
+            "{} {}",
+            block.class.subject_course,
+            crns(block.class, classes)
+        ),
+        format!("{:#}-{:#}", block.start, block.end),
+    ];
+
+    let dates = (block.meeting.start_date, block.meeting.end_date);
+    if dates != term {
+        if let (Some(start), Some(end)) = dates {
+            lines.push(format!("{}-{}", start.format("%m/%d"), end.format("%m/%d")));
+        }
+    }
+
+    lines
+}
+
+// The section's CRN then its alternates (ex: `10123 (10124 10125)`)
+fn crns(class: &Class, classes: &[(&Class, Vec<&Class>)]) -> String {
+    let alts = classes
+        .iter()
+        .find(|(it, _)| it.crn == class.crn)
+        .map(|(_, alts)| {
+            alts.iter()
+                .map(|alt| alt.crn)
+                .filter(|crn| *crn != class.crn)
+                .join(" ")
+        })
+        .unwrap_or_default();
+
+    if alts.is_empty() {
+        class.crn.to_string()
+    } else {
+        format!("{} ({alts})", class.crn)
+    }
+}
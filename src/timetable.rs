@@ -0,0 +1,157 @@
+use crate::{solver, Class, Crn, Day, Session, Time};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Where every meeting of a schedule goes on a weekly grid, shared by everything that draws one
+///
+/// Positions are fractions of the grid so each painter can scale them to its own size. Meetings
+/// that overlap in time, like half term sections, sit side by side in lanes of their day.
+#[derive(Clone, Debug)]
+pub struct Timetable<'a> {
+    /// First and last hour on the grid, `None` when nothing has a set time
+    pub hours: Option<(u8, u8)>,
+    pub blocks: Vec<TimetableBlock<'a>>,
+    /// Classes with meetings that have no set time
+    pub untimed: Vec<&'a Class>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TimetableBlock<'a> {
+    pub class: &'a Class,
+    pub meeting: &'a Session,
+    pub day: Day,
+    pub start: Time,
+    pub end: Time,
+    /// Which of the `lanes` side by side meetings this is
+    pub lane: usize,
+    pub lanes: usize,
+}
+
+impl<'a> Timetable<'a> {
+    pub fn new(classes: &[&'a Class]) -> Self {
+        let mut blocks = Vec::new();
+        for class in classes {
+            for meeting in class.meetings.iter().filter(|it| !it.excluded) {
+                let Some((start, end)) = meeting.times() else {
+                    continue;
+                };
+
+                for day in meeting.days.iter() {
+                    blocks.push(TimetableBlock {
+                        class,
+                        meeting,
+                        day,
+                        start,
+                        end,
+                        lane: 0,
+                        lanes: 1,
+                    });
+                }
+            }
+        }
+        blocks.sort_by_key(|it| (it.day, it.start.minutes(), it.end.minutes(), it.class.crn));
+        assign_lanes(&mut blocks, 1);
+
+        let first = blocks.iter().map(|it| it.start.minutes()).min();
+        let last = blocks.iter().map(|it| it.end.minutes()).max();
+        let hours = first
+            .zip(last)
+            .map(|(first, last)| ((first / 60) as u8, last.div_ceil(60) as u8));
+
+        Self {
+            hours,
+            blocks,
+            untimed: classes
+                .iter()
+                .filter(|class| !solver::has_times(class))
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Also gives meetings their own lane when they only share a row of `minutes`, for grids
+    /// that round every meeting out to whole rows
+    pub fn round_lanes(&mut self, minutes: u16) {
+        assign_lanes(&mut self.blocks, minutes.max(1));
+    }
+
+    /// Every hour line from the first to the last
+    pub fn hour_lines(&self) -> impl Iterator<Item = u8> {
+        self.hours
+            .into_iter()
+            .flat_map(|(first, last)| first..=last)
+    }
+
+    /// How far down the grid `time` is, from 0 at the first hour to 1 at the last
+    pub fn y(&self, time: Time) -> f32 {
+        match self.hours {
+            Some((first, last)) if last > first => {
+                (time.minutes() as f32 - first as f32 * 60.0) / ((last - first) as f32 * 60.0)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+impl TimetableBlock<'_> {
+    /// Left, top, right and bottom as fractions of the grid, Sunday in the first of seven columns
+    pub fn rect(&self, timetable: &Timetable) -> [f32; 4] {
+        let width = 1.0 / 7.0 / self.lanes as f32;
+        let left = self.day as usize as f32 / 7.0 + self.lane as f32 * width;
+
+        [
+            left,
+            timetable.y(self.start),
+            left + width,
+            timetable.y(self.end),
+        ]
+    }
+}
+
+// Meetings overlapping others on the same day get their own lane, every meeting in a run of
+// overlaps splits the column the same way. Times are rounded out to `round` minutes first
+fn assign_lanes(blocks: &mut [TimetableBlock], round: u16) {
+    let start_of = |block: &TimetableBlock| block.start.minutes() / round * round;
+    let end_of = |block: &TimetableBlock| block.end.minutes().div_ceil(round) * round;
+
+    let mut start = 0;
+    while start < blocks.len() {
+        let mut run_end = end_of(&blocks[start]);
+        let mut end = start + 1;
+        while end < blocks.len()
+            && blocks[end].day == blocks[start].day
+            && start_of(&blocks[end]) < run_end
+        {
+            run_end = run_end.max(end_of(&blocks[end]));
+            end += 1;
+        }
+
+        let mut lane_ends: Vec<u16> = Vec::new();
+        for block in &mut blocks[start..end] {
+            let free = lane_ends.iter().position(|it| *it <= start_of(block));
+            block.lane = free.unwrap_or(lane_ends.len());
+            if block.lane == lane_ends.len() {
+                lane_ends.push(end_of(block));
+            } else {
+                lane_ends[block.lane] = end_of(block);
+            }
+        }
+        for block in &mut blocks[start..end] {
+            block.lanes = lane_ends.len();
+        }
+
+        start = end;
+    }
+}
+
+/// Red, green and blue for a section, the same every time it's drawn
+pub fn color(crn: Crn) -> [u8; 3] {
+    let mut hasher = DefaultHasher::new();
+    crn.hash(&mut hasher);
+    let hash = hasher.finish();
+    [
+        (hash >> 16 & 0xFF) as u8,
+        (hash >> 8 & 0xFF) as u8,
+        (hash & 0xFF) as u8,
+    ]
+}
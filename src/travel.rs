@@ -0,0 +1,146 @@
+use crate::{Block, Place, Schedule};
+use fxhash::FxHashMap as HashMap;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// How long it takes to get from one place to another, meetings in different places closer than
+/// this can't be taken together
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(default)]
+pub struct TravelTimes {
+    /// Minutes between two buildings of the same campus
+    pub building: u16,
+    /// Minutes between two campuses
+    pub campus: u16,
+    /// Overrides for pairs of campuses or buildings, either way around
+    pub times: Vec<TravelTime>,
+}
+
+/// Minutes between two campus names (ex: `Boca Raton` and `Jupiter`) or building codes
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct TravelTime {
+    pub from: String,
+    pub to: String,
+    pub minutes: u16,
+}
+
+impl TravelTimes {
+    /// Nothing takes any time to get to
+    pub fn is_empty(&self) -> bool {
+        self.building == 0 && self.campus == 0 && self.times.iter().all(|it| it.minutes == 0)
+    }
+}
+
+impl Display for TravelTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} to {}: {}min", self.from, self.to, self.minutes)
+    }
+}
+
+/// [`TravelTimes`] looked up by [`Place`]
+#[derive(Clone, Debug, Default)]
+pub struct Travel {
+    building: u16,
+    campus: u16,
+    times: HashMap<(u32, u32), u16>,
+}
+
+impl Travel {
+    pub fn new(times: &TravelTimes) -> Self {
+        let mut pairs = HashMap::default();
+        for time in &times.times {
+            let (from, to) = (Place::key(&time.from), Place::key(&time.to));
+            pairs.insert((from, to), time.minutes);
+            pairs.insert((to, from), time.minutes);
+        }
+
+        Self {
+            building: times.building,
+            campus: times.campus,
+            times: pairs,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.building == 0 && self.campus == 0 && self.times.values().all(|it| *it == 0)
+    }
+
+    /// Minutes to get from `a` to `b`, nothing when either isn't known
+    ///
+    /// Listed buildings come first, then listed campuses, then the defaults.
+    pub fn minutes(&self, a: Place, b: Place) -> u16 {
+        if a == b {
+            return 0;
+        }
+
+        let buildings = a.building != 0 && b.building != 0;
+        if buildings {
+            if let Some(minutes) = self.times.get(&(a.building, b.building)) {
+                return *minutes;
+            }
+        }
+
+        if a.campus != 0 && b.campus != 0 && a.campus != b.campus {
+            self.times
+                .get(&(a.campus, b.campus))
+                .copied()
+                .unwrap_or(self.campus)
+        } else if buildings && a.building != b.building {
+            self.building
+        } else {
+            0
+        }
+    }
+
+    // Both meet on the same dates without enough time to get from one to the other
+    fn too_close(&self, a: &Block, b: &Block) -> bool {
+        if !a.2.overlaps(&b.2) {
+            return false;
+        }
+
+        let (first, second) = if a.0 <= b.0 { (a, b) } else { (b, a) };
+        let gap = second.0 as i32 - (first.0 + first.1) as i32;
+        gap < self.minutes(a.3, b.3) as i32
+    }
+
+    /// Same as [`Schedule::overlaps`], also failing when there isn't time to get between two
+    /// meetings
+    pub fn conflicts(&self, schedule: &Schedule, others: &[&Schedule]) -> bool {
+        if schedule.overlaps(others) {
+            return true;
+        }
+        if self.is_empty() {
+            return false;
+        }
+
+        let data = schedule.data();
+        others.iter().any(|other| {
+            let other = other.data();
+            (0..7).any(|day| {
+                data[day]
+                    .iter()
+                    .cartesian_product(other[day])
+                    .any(|(a, b)| self.too_close(a, b))
+            })
+        })
+    }
+
+    /// Minutes short of twice the travel time between back to back meetings in different places,
+    /// over a sorted schedule
+    pub fn tight_transfers(&self, schedule: &[&[Block]; 7]) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        schedule
+            .iter()
+            .flat_map(|day| day.iter().tuple_windows())
+            .filter(|(a, b): &(&Block, &Block)| a.2.overlaps(&b.2))
+            .map(|(a, b)| {
+                let gap = b.0.saturating_sub(a.0 + a.1);
+                (self.minutes(a.3, b.3) * 2).saturating_sub(gap) as f64
+            })
+            .sum()
+    }
+}
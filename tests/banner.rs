@@ -0,0 +1,466 @@
+use schedual::banner::{self, ConversionReport, ConvertArgs, SectionError, Strictness};
+use schedual::{CreditHours, CrossList, Days, Enrollment, Faculty, Schedule, Time};
+use serde_json::{json, Value};
+
+// A section as Banner's `searchResults` sends it
+fn fixture() -> Value {
+    json!({
+        "campusDescription": "Boca Raton",
+        "courseReferenceNumber": "10123",
+        "courseNumber": "2048",
+        "courseTitle": "General Physics 1",
+        "creditHourHigh": null,
+        "creditHourLow": 3,
+        "creditHours": 3,
+        "crossList": 42,
+        "crossListAvailable": 5,
+        "crossListCapacity": 60,
+        "crossListCount": 55,
+        "enrollment": 28,
+        "maximumEnrollment": 30,
+        "seatsAvailable": 2,
+        "waitCount": 1,
+        "waitCapacity": 10,
+        "waitAvailable": 9,
+        "faculty": [{
+            "displayName": "Smith, Jane",
+            "emailAddress": "jsmith@fau.edu",
+            "primaryIndicator": true
+        }],
+        "instructionalMethodDescription": "Classroom",
+        "meetingsFaculty": [{
+            "meetingTime": {
+                "beginTime": "0900",
+                "endTime": "0950",
+                "startDate": "08/21/2023",
+                "endDate": "12/08/2023",
+                "monday": true,
+                "tuesday": false,
+                "wednesday": true,
+                "thursday": false,
+                "friday": true,
+                "saturday": false,
+                "sunday": false,
+                "building": "SE",
+                "buildingDescription": "Science and Engineering",
+                "room": "101",
+                "meetingTypeDescription": "Class"
+            }
+        }],
+        "openSection": true,
+        "partOfTermDescription": "1",
+        "scheduleTypeDescription": "Lecture",
+        "sequenceNumber": "001",
+        "specialApprovalDescription": null,
+        "subjectCourse": "PHY2048",
+        "subjectDescription": "Physics",
+        "termDesc": "202380"
+    })
+}
+
+fn with(mut section: Value, path: &[&str], value: Value) -> Value {
+    let (last, parents) = path.split_last().unwrap();
+    let mut current = &mut section;
+    for key in parents {
+        current = match key.parse::<usize>() {
+            Ok(idx) => &mut current[idx],
+            Err(_) => &mut current[*key],
+        };
+    }
+    current[*last] = value;
+    section
+}
+
+fn error(field: &str, reason: &str) -> SectionError {
+    SectionError {
+        index: 0,
+        crn: Some("10123".to_owned()),
+        field: field.to_owned(),
+        reason: reason.to_owned(),
+    }
+}
+
+#[test]
+fn sections_convert_to_classes() {
+    let (class, defaulted) = banner::convert_section(0, &fixture(), Strictness::Strict).unwrap();
+    assert!(defaulted.is_empty());
+    assert_eq!(
+        banner::convert_section(0, &fixture(), Strictness::Lenient).unwrap(),
+        (class.clone(), Vec::new())
+    );
+
+    assert_eq!(class.crn, 10123);
+    assert_eq!(class.campus, "Boca Raton");
+    assert_eq!(class.subject_course, "PHY2048");
+    assert_eq!(class.course_number, "2048");
+    assert_eq!(class.name, "General Physics 1");
+    assert_eq!(
+        class.credit_hours,
+        CreditHours {
+            credit_hour_high: None,
+            credit_hour_low: Some(3),
+            credit_hours: Some(3),
+        }
+    );
+    assert_eq!(
+        class.cross_list,
+        Some(CrossList {
+            cross_list: 42,
+            cross_list_available: 5,
+            cross_list_capacity: 60,
+            cross_list_count: 55,
+        })
+    );
+    assert_eq!(
+        class.enrollment,
+        Enrollment {
+            count: 28,
+            capacity: 30,
+            available: 2,
+        }
+    );
+    assert_eq!(
+        class.wait_list,
+        Enrollment {
+            count: 1,
+            capacity: 10,
+            available: 9,
+        }
+    );
+    assert_eq!(
+        class.faculty,
+        vec![Faculty {
+            name: "Smith, Jane".to_owned(),
+            email: Some("jsmith@fau.edu".to_owned()),
+            primary: true,
+        }]
+    );
+    assert!(class.open);
+    assert_eq!(class.sequence_number, "001");
+    assert_eq!(class.special_approval, None);
+
+    let meeting = &class.meetings[0];
+    assert_eq!(class.meetings.len(), 1);
+    assert_eq!(meeting.start_time, Some(Time::new(9, 0)));
+    assert_eq!(meeting.end_time, Some(Time::new(9, 50)));
+    assert_eq!(meeting.days, Days::mwf());
+    assert_eq!(meeting.building_code.as_deref(), Some("SE"));
+    assert_eq!(meeting.room, Some(101));
+    assert_eq!(meeting.meeting_type, "Class");
+    assert_ne!(class.schedule, Schedule::default());
+}
+
+#[test]
+fn missing_fields_name_the_crn_and_field() {
+    let section = with(fixture(), &["campusDescription"], Value::Null);
+    let error = banner::convert_section(0, &section, Strictness::Strict).unwrap_err();
+    assert_eq!(error, self::error("campusDescription", "missing"));
+    assert_eq!(
+        error.to_string(),
+        "CRN 10123, field `campusDescription`: missing"
+    );
+
+    let section = with(
+        fixture(),
+        &["meetingsFaculty", "0", "meetingTime", "monday"],
+        Value::Null,
+    );
+    assert_eq!(
+        banner::convert_section(0, &section, Strictness::Strict).unwrap_err(),
+        self::error("meetingsFaculty[0].meetingTime.monday", "missing")
+    );
+
+    let section = with(fixture(), &["enrollment"], json!("lots"));
+    let error = banner::convert_section(0, &section, Strictness::Strict).unwrap_err();
+    assert_eq!(error.field, "enrollment");
+    assert_eq!(error.crn.as_deref(), Some("10123"));
+
+    let section = with(fixture(), &["courseReferenceNumber"], json!("CRN"));
+    assert_eq!(
+        banner::convert_section(3, &section, Strictness::Lenient).unwrap_err(),
+        SectionError {
+            index: 3,
+            crn: Some("CRN".to_owned()),
+            field: "courseReferenceNumber".to_owned(),
+            reason: "`CRN` is not a number".to_owned(),
+        }
+    );
+
+    let section = with(fixture(), &["courseReferenceNumber"], Value::Null);
+    let error = banner::convert_section(3, &section, Strictness::Lenient).unwrap_err();
+    assert_eq!(error.crn, None);
+    assert_eq!(
+        error.to_string(),
+        "Section #3, field `courseReferenceNumber`: missing"
+    );
+}
+
+#[test]
+fn conversions_collect_every_failed_section() {
+    let other = with(fixture(), &["courseReferenceNumber"], json!("10124"));
+    let sections = [fixture(), with(other, &["subjectCourse"], Value::Null)];
+
+    let conversion = banner::convert(&sections, Strictness::Strict);
+    assert_eq!(
+        conversion.bank.keys().copied().collect::<Vec<_>>(),
+        vec![10123]
+    );
+    assert_eq!(conversion.errors.len(), 1);
+    assert_eq!(conversion.errors[0].index, 1);
+
+    let error = conversion.into_bank().unwrap_err().to_string();
+    assert!(error.contains("CRN 10124, field `subjectCourse`: missing"));
+}
+
+#[test]
+fn lenient_sections_default_what_they_can() {
+    let section = with(fixture(), &["campusDescription"], Value::Null);
+    let section = with(section, &["waitCount"], Value::Null);
+
+    let (class, defaulted) = banner::convert_section(0, &section, Strictness::Lenient).unwrap();
+    assert_eq!(class.campus, "");
+    assert_eq!(class.wait_list.count, 0);
+    assert_eq!(
+        defaulted,
+        vec![
+            error("waitCount", "missing, defaulted to 0"),
+            error("campusDescription", "missing, defaulted to \"\""),
+        ]
+    );
+
+    // Nothing to go on without a subject
+    let section = with(fixture(), &["subjectCourse"], Value::Null);
+    assert_eq!(
+        banner::convert_section(0, &section, Strictness::Lenient).unwrap_err(),
+        error("subjectCourse", "missing")
+    );
+}
+
+#[test]
+fn meetings_without_times_are_dropped_when_lenient() {
+    let section = with(
+        fixture(),
+        &["meetingsFaculty", "0", "meetingTime"],
+        Value::Null,
+    );
+
+    assert_eq!(
+        banner::convert_section(0, &section, Strictness::Strict).unwrap_err(),
+        error("meetingsFaculty[0].meetingTime", "missing")
+    );
+
+    let (class, defaulted) = banner::convert_section(0, &section, Strictness::Lenient).unwrap();
+    assert!(class.meetings.is_empty());
+    assert_eq!(
+        defaulted,
+        vec![error(
+            "meetingsFaculty[0].meetingTime",
+            "missing, meeting dropped"
+        )]
+    );
+}
+
+#[test]
+fn malformed_fields_are_removed_when_lenient() {
+    let section = with(fixture(), &["enrollment"], json!("lots"));
+    let (class, defaulted) = banner::convert_section(0, &section, Strictness::Lenient).unwrap();
+    assert_eq!(class.enrollment.count, 0);
+    assert_eq!(
+        defaulted,
+        vec![
+            error(
+                "enrollment",
+                "invalid type: string \"lots\", expected u64, removed"
+            ),
+            error("enrollment", "missing, defaulted to 0"),
+        ]
+    );
+
+    // Nothing to go on without a subject, even once the bad value is removed
+    let section = with(fixture(), &["subjectCourse"], json!(5));
+    let error = banner::convert_section(0, &section, Strictness::Lenient).unwrap_err();
+    assert_eq!(error, self::error("subjectCourse", "missing"));
+}
+
+#[test]
+fn malformed_nested_values_are_removed_when_lenient() {
+    let section = with(
+        fixture(),
+        &["meetingsFaculty", "0", "meetingTime", "monday"],
+        json!("yes"),
+    );
+    let (class, defaulted) = banner::convert_section(0, &section, Strictness::Lenient).unwrap();
+    assert_eq!(
+        class.meetings[0].days,
+        Days {
+            monday: false,
+            ..Days::mwf()
+        }
+    );
+    assert_eq!(
+        defaulted,
+        vec![
+            error(
+                "meetingsFaculty[0].meetingTime.monday",
+                "invalid type: string \"yes\", expected a boolean, removed"
+            ),
+            error(
+                "meetingsFaculty[0].meetingTime.monday",
+                "missing, defaulted to false"
+            ),
+        ]
+    );
+
+    let mut section = fixture();
+    section["faculty"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!("Doe, John"));
+    let (class, defaulted) = banner::convert_section(0, &section, Strictness::Lenient).unwrap();
+    assert_eq!(class.faculty.len(), 1);
+    assert_eq!(
+        defaulted,
+        vec![error(
+            "faculty[1]",
+            "invalid type: string \"Doe, John\", expected struct BannerFaculty, removed"
+        )]
+    );
+}
+
+// One good section and one without a subject
+fn sections() -> Vec<Value> {
+    let other = with(fixture(), &["courseReferenceNumber"], json!("10124"));
+    vec![fixture(), with(other, &["subjectCourse"], json!(5))]
+}
+
+#[test]
+fn reports_list_skipped_sections() {
+    let folder = std::env::temp_dir().join(format!("schedual-{}-banner", std::process::id()));
+    let _ = std::fs::remove_dir_all(&folder);
+    std::fs::create_dir_all(&folder).unwrap();
+    let path = folder.join("report.json");
+
+    let args = ConvertArgs {
+        lenient: true,
+        report: Some(path.clone()),
+        ..Default::default()
+    };
+    let (bank, converted) = args.convert(&sections()).unwrap();
+    assert_eq!(bank.keys().copied().collect::<Vec<_>>(), vec![10123]);
+    assert_eq!(
+        converted.to_string(),
+        "Converted 1 of 2 sections (1 skipped, 0 fields defaulted)"
+    );
+
+    let report: ConversionReport =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(report.strictness, Strictness::Lenient);
+    assert_eq!((report.total, report.converted), (2, 1));
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].index, 1);
+    assert_eq!(report.skipped[0].crn.as_deref(), Some("10124"));
+    assert_eq!(report.skipped[0].field, "subjectCourse");
+    assert!(report.defaulted.is_empty());
+
+    std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn reports_count_excluded_meetings() {
+    let args = ConvertArgs {
+        exclusions: vec!["type:Class".parse().unwrap()],
+        max_skipped: Some(1),
+        ..Default::default()
+    };
+    let (bank, report) = args.convert(&sections()).unwrap();
+
+    assert!(bank[&10123].meetings[0].excluded);
+    assert_eq!(report.excluded, 1);
+    assert_eq!(
+        report.to_string(),
+        "Converted 1 of 2 sections (1 skipped, 0 fields defaulted)\nExcluded 1 meetings"
+    );
+}
+
+#[test]
+fn too_many_skipped_sections_fail() {
+    let args = ConvertArgs {
+        lenient: true,
+        max_skipped: Some(0),
+        ..Default::default()
+    };
+    let error = args.convert(&sections()).unwrap_err().to_string();
+    assert!(error.starts_with("1 of 2 sections could not be converted (at most 0 allowed)"));
+    assert!(error.contains("CRN 10124, field `subjectCourse`: missing"));
+
+    let args = ConvertArgs {
+        max_skipped: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(args.convert(&sections()).unwrap().0.len(), 1);
+
+    // Strict conversions allow none by default
+    assert!(ConvertArgs::default().convert(&sections()).is_err());
+}
+
+#[test]
+fn unreadable_times_and_dates_are_reported() {
+    let meeting = |field: &str| format!("meetingsFaculty[0].meetingTime.{field}");
+    let section = with(
+        fixture(),
+        &["meetingsFaculty", "0", "meetingTime", "beginTime"],
+        json!("9am"),
+    );
+    let section = with(
+        section,
+        &["meetingsFaculty", "0", "meetingTime", "endDate"],
+        json!("12/45/2023"),
+    );
+
+    assert_eq!(
+        banner::convert_section(0, &section, Strictness::Strict).unwrap_err(),
+        error(&meeting("beginTime"), "Bad time: 9am")
+    );
+
+    let (class, defaulted) = banner::convert_section(0, &section, Strictness::Lenient).unwrap();
+    assert_eq!(class.meetings[0].start_time, None);
+    assert_eq!(class.meetings[0].end_date, None);
+    assert_eq!(
+        defaulted,
+        vec![
+            error(&meeting("beginTime"), "Bad time: 9am, left open"),
+            error(&meeting("endDate"), "Bad date: 12/45/2023, left open"),
+        ]
+    );
+
+    // Sections without set times send none
+    let section = with(
+        fixture(),
+        &["meetingsFaculty", "0", "meetingTime", "beginTime"],
+        Value::Null,
+    );
+    let section = with(
+        section,
+        &["meetingsFaculty", "0", "meetingTime", "endTime"],
+        Value::Null,
+    );
+    let (class, defaulted) = banner::convert_section(0, &section, Strictness::Strict).unwrap();
+    assert_eq!(class.meetings[0].start_time, None);
+    assert!(defaulted.is_empty());
+}
+
+#[test]
+fn missing_faculty_and_meetings_are_not_empty() {
+    for field in ["faculty", "meetingsFaculty"] {
+        let mut section = fixture();
+        section.as_object_mut().unwrap().remove(field);
+
+        assert_eq!(
+            banner::convert_section(0, &section, Strictness::Strict).unwrap_err(),
+            error(field, "missing")
+        );
+
+        let (_, defaulted) = banner::convert_section(0, &section, Strictness::Lenient).unwrap();
+        assert_eq!(defaulted, vec![error(field, "missing, defaulted to []")]);
+    }
+}
@@ -0,0 +1,180 @@
+#![allow(dead_code)]
+
+use chrono::NaiveDate;
+use schedual::plan::Plan;
+use schedual::solver::Include;
+use schedual::{
+    Class, ClassBank, CreditHours, Crn, Days, Enrollment, Faculty, Schedule, Session, Time,
+};
+
+pub fn date(month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2023, month, day).unwrap()
+}
+
+/// A lecture meeting for the whole term
+pub fn session(days: Days, start: Time, end: Time) -> Session {
+    Session {
+        start_time: Some(start),
+        end_time: Some(end),
+        start_date: Some(date(8, 21)),
+        end_date: Some(date(12, 8)),
+        days,
+        building_code: None,
+        building_name: None,
+        room: None,
+        meeting_type: "Class".to_owned(),
+        excluded: false,
+    }
+}
+
+pub fn class(crn: Crn, subject_course: &str, meetings: Vec<Session>) -> Class {
+    Class {
+        campus: "Boca Raton".to_owned(),
+        crn,
+        course_number: subject_course.chars().skip(3).collect(),
+        name: subject_course.to_owned(),
+        credit_hours: CreditHours {
+            credit_hour_high: None,
+            credit_hour_low: Some(3),
+            credit_hours: Some(3),
+        },
+        cross_list: None,
+        enrollment: Enrollment {
+            count: 0,
+            capacity: 30,
+            available: 30,
+        },
+        wait_list: Enrollment {
+            count: 0,
+            capacity: 0,
+            available: 0,
+        },
+        faculty: Vec::new(),
+        instructional_method: "Classroom".to_owned(),
+        schedule: Schedule::from_meetings("Boca Raton", &meetings),
+        meetings,
+        open: true,
+        part_of_term: "1".to_owned(),
+        schedule_type: "Lecture".to_owned(),
+        sequence_number: "001".to_owned(),
+        special_approval: None,
+        subject_course: subject_course.to_owned(),
+        subject_description: String::new(),
+        term: "202380".to_owned(),
+    }
+}
+
+/// An online meeting without set days or times
+pub fn untimed() -> Session {
+    Session {
+        start_time: None,
+        end_time: None,
+        ..session(Days::never(), Time::new(0, 0), Time::new(0, 0))
+    }
+}
+
+/// A lecture meeting on `days` for the first 50 minutes of `hour`
+pub fn lecture(days: Days, hour: u8) -> Session {
+    session(days, Time::new(hour, 0), Time::new(hour, 50))
+}
+
+/// A [`lecture`] meeting MWF
+pub fn mwf(hour: u8) -> Session {
+    lecture(Days::mwf(), hour)
+}
+
+/// A section with a single [`mwf`] meeting
+pub fn section(crn: Crn, subject_course: &str, hour: u8) -> Class {
+    class(crn, subject_course, vec![mwf(hour)])
+}
+
+/// Moves every meeting of the class to `building` on `campus`
+pub fn located(class: Class, campus: &str, building: &str) -> Class {
+    let meetings = class
+        .meetings
+        .into_iter()
+        .map(|meeting| Session {
+            building_code: Some(building.to_owned()),
+            ..meeting
+        })
+        .collect::<Vec<_>>();
+
+    Class {
+        campus: campus.to_owned(),
+        schedule: Schedule::from_meetings(campus, &meetings),
+        meetings,
+        ..class
+    }
+}
+
+pub fn sequenced(class: Class, sequence: &str) -> Class {
+    Class {
+        sequence_number: sequence.to_owned(),
+        ..class
+    }
+}
+
+pub fn delivered(class: Class, method: &str) -> Class {
+    Class {
+        instructional_method: method.to_owned(),
+        ..class
+    }
+}
+
+pub fn taught_by(class: Class, faculty: &[&str]) -> Class {
+    Class {
+        faculty: faculty
+            .iter()
+            .map(|name| Faculty {
+                name: name.to_string(),
+                email: None,
+                primary: true,
+            })
+            .collect(),
+        ..class
+    }
+}
+
+pub fn bank_of(classes: impl IntoIterator<Item = Class>) -> ClassBank {
+    classes
+        .into_iter()
+        .map(|class| (class.crn, class))
+        .collect()
+}
+
+/// A plan taking `includes`, everything else left at its default
+pub fn plan_with(includes: &[Include]) -> Plan {
+    Plan {
+        includes: includes.to_vec(),
+        ..Default::default()
+    }
+}
+
+pub fn course(subject: &str) -> Include {
+    Include::Course {
+        subject: subject.to_owned(),
+        course_type: None,
+    }
+}
+
+/// The best `count` schedules `plan` finds in `bank` with their scores, in rank order
+pub fn best(bank: &ClassBank, plan: &Plan, count: usize) -> Vec<(f64, Vec<Crn>)> {
+    plan.solve(bank, count, 2)
+        .schedules
+        .into_iter()
+        .map(|((score, _), crns)| (score, crns))
+        .collect()
+}
+
+/// The CRNs of the best `count` schedules, sorted so ties in rank don't matter
+pub fn schedules(bank: &ClassBank, plan: &Plan, count: usize) -> Vec<Vec<Crn>> {
+    let mut found = best(bank, plan, count)
+        .into_iter()
+        .map(|(_, mut crns)| {
+            crns.sort();
+            crns
+        })
+        .collect::<Vec<_>>();
+    found.sort();
+    found
+}
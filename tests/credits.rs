@@ -0,0 +1,121 @@
+mod common;
+
+use common::{bank_of, course, plan_with, schedules, section};
+use schedual::plan::Plan;
+use schedual::solver::{Constraint, CreditBounds, Include};
+use schedual::{diagnosis, Class, ClassBank, CreditHours, Crn};
+
+fn optional(subject: &str) -> Include {
+    Include::Optional {
+        include: Box::new(course(subject)),
+    }
+}
+
+fn credits(low: Option<u64>, high: Option<u64>, fixed: Option<u64>) -> CreditHours {
+    CreditHours {
+        credit_hour_high: high,
+        credit_hour_low: low,
+        credit_hours: fixed,
+    }
+}
+
+fn bank() -> ClassBank {
+    let fixed = |hours| credits(Some(hours), None, Some(hours));
+    let section = |crn, subject, hour, credit_hours| Class {
+        credit_hours,
+        ..section(crn, subject, hour)
+    };
+
+    bank_of([
+        section(1, "PHY2048", 8, fixed(4)),
+        section(2, "MAC2313", 9, fixed(4)),
+        section(3, "ENC1101", 10, fixed(3)),
+        section(4, "LIT2010", 11, fixed(3)),
+        section(5, "MUL2010", 12, fixed(3)),
+        // Variable credit research hours
+        section(6, "IDS4910", 13, credits(Some(1), Some(3), None)),
+    ])
+}
+
+fn includes() -> Vec<Include> {
+    vec![
+        course("PHY2048"),
+        course("MAC2313"),
+        optional("ENC1101"),
+        optional("LIT2010"),
+        optional("MUL2010"),
+        optional("IDS4910"),
+    ]
+}
+
+fn range(crns: &[Crn]) -> (u64, u64) {
+    let bank = bank();
+    crns.iter()
+        .map(|crn| bank[crn].credit_hours.range())
+        .fold((0, 0), |(low, high), it| (low + it.0, high + it.1))
+}
+
+#[test]
+fn credit_ranges() {
+    assert_eq!(credits(Some(3), None, Some(3)).range(), (3, 3));
+    assert_eq!(credits(Some(1), Some(4), None).range(), (1, 4));
+    assert_eq!(credits(None, None, Some(2)).range(), (2, 2));
+    assert_eq!(credits(None, None, None).range(), (0, 0));
+}
+
+#[test]
+fn schedules_stay_within_bounds() {
+    let everything = schedules(&bank(), &plan_with(&includes()), 100);
+    assert_eq!(everything.len(), 16);
+
+    let plan = Plan {
+        constraints: vec![Constraint::Credits { min: 15, max: 17 }],
+        ..plan_with(&includes())
+    };
+    let bounds = CreditBounds::of(&plan.constraints);
+    let expected = everything
+        .into_iter()
+        .filter(|crns| bounds.allows(range(crns)))
+        .collect::<Vec<_>>();
+
+    assert_eq!(schedules(&bank(), &plan, 100), expected);
+    // 8 required credits, then three courses or two of them with the research hours
+    assert!(expected.contains(&vec![1, 2, 3, 4, 5]));
+    assert!(expected.contains(&vec![1, 2, 3, 4, 6]));
+    assert!(!expected.contains(&vec![1, 2, 3, 4]));
+    assert!(!expected.contains(&vec![1, 2, 3, 4, 5, 6]));
+}
+
+#[test]
+fn variable_credits_reach_either_end() {
+    let includes = [course("PHY2048"), course("IDS4910")];
+
+    let best = |min, max| {
+        let plan = Plan {
+            constraints: vec![Constraint::Credits { min, max }],
+            ..plan_with(&includes)
+        };
+        schedules(&bank(), &plan, 100)
+    };
+
+    assert_eq!(best(0, 5), vec![vec![1, 6]]);
+    assert_eq!(best(7, 20), vec![vec![1, 6]]);
+    assert!(best(8, 20).is_empty());
+}
+
+#[test]
+fn diagnosis_reports_unreachable_credits() {
+    let diagnose = |min, max| {
+        let plan = Plan {
+            constraints: vec![Constraint::Credits { min, max }],
+            ..plan_with(&includes())
+        };
+        diagnosis::diagnose(&bank(), &plan)
+    };
+
+    let diagnosis = diagnose(24, 30);
+    assert_eq!(diagnosis.credits, Some((8, 20)));
+    assert!(!diagnosis.is_feasible());
+
+    assert!(diagnose(12, 18).is_feasible());
+}
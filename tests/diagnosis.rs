@@ -0,0 +1,165 @@
+mod common;
+
+use common::{
+    bank_of, class, course, delivered, lecture, located, plan_with, section, sequenced, session,
+    untimed,
+};
+use schedual::diagnosis::{self, Diagnosis};
+use schedual::link::{Link, LinkRule};
+use schedual::plan::Plan;
+use schedual::solver::{Constraint, Include};
+use schedual::travel::TravelTimes;
+use schedual::{ClassBank, Days, Delivery, Time};
+
+fn bank() -> ClassBank {
+    bank_of([
+        section(1, "PHY2048", 9),
+        class(
+            2,
+            "MAC2313",
+            vec![session(Days::mwf(), Time::new(9, 5), Time::new(9, 55))],
+        ),
+        section(3, "MAC2313", 11),
+        section(4, "LIT2010", 14),
+        section(5, "CHM2045", 11),
+        class(
+            6,
+            "ENC1101",
+            vec![session(
+                Days::from_letters("S").unwrap(),
+                Time::new(9, 0),
+                Time::new(12, 0),
+            )],
+        ),
+        delivered(class(7, "ENC1101", vec![untimed()]), "Fully Online"),
+    ])
+}
+
+fn diagnose(includes: &[Include], constraints: &[Constraint]) -> Diagnosis {
+    let plan = Plan {
+        constraints: constraints.to_vec(),
+        ..plan_with(includes)
+    };
+    diagnosis::diagnose(&bank(), &plan)
+}
+
+#[test]
+fn finds_the_courses_that_always_conflict() {
+    let includes = [
+        course("PHY2048"),
+        course("LIT2010"),
+        course("MAC2313"),
+        course("CHM2045"),
+    ];
+    let diagnosis = diagnose(&includes, &[]);
+
+    assert_eq!(diagnosis.emptied().count(), 0);
+    assert_eq!(
+        diagnosis.conflicting,
+        vec![course("PHY2048"), course("MAC2313"), course("CHM2045")]
+    );
+    assert!(!diagnosis.is_feasible());
+}
+
+#[test]
+fn blames_constraints() {
+    let includes = [course("PHY2048"), course("ENC1101"), course("BIO1010")];
+    let constraints = [
+        Constraint::BlockDays {
+            days: Days::from_letters("SU").unwrap(),
+        },
+        Constraint::InstructionalMethod {
+            allow: vec![Delivery::InPerson],
+        },
+    ];
+    let diagnosis = diagnose(&includes, &constraints);
+
+    let emptied = diagnosis.emptied().collect::<Vec<_>>();
+    assert_eq!(emptied.len(), 2);
+
+    assert_eq!(emptied[0].include, course("ENC1101"));
+    assert_eq!(emptied[0].matched, 2);
+    assert_eq!(
+        emptied[0].constraints,
+        vec![(constraints[0].clone(), 1), (constraints[1].clone(), 1)]
+    );
+
+    assert_eq!(emptied[1].include, course("BIO1010"));
+    assert_eq!(emptied[1].matched, 0);
+
+    assert!(diagnosis.conflicting.is_empty());
+    assert!(diagnosis
+        .to_string()
+        .contains("No classes left for ENC1101: 2 matched, 1 removed by blocked days US, 1 removed by in person sections, 0 left"));
+}
+
+#[test]
+fn feasible_plans_have_nothing_to_report() {
+    let includes = [course("PHY2048"), course("LIT2010"), course("MAC2313")];
+    let diagnosis = diagnose(&includes, &[]);
+
+    assert!(diagnosis.is_feasible());
+    assert!(diagnosis.conflicting.is_empty());
+}
+
+#[test]
+fn links_that_never_pair_conflict() {
+    let tuesday = lecture(Days::from_letters("T").unwrap(), 9);
+    let bank = bank_of([
+        sequenced(class(1, "PHY2048", vec![tuesday.clone()]), "01"),
+        sequenced(class(2, "PHY2048L", vec![tuesday]), "01A"),
+        sequenced(section(3, "PHY2048L", 9), "02A"),
+    ]);
+
+    let unlinked = plan_with(&[course("PHY2048"), course("PHY2048L")]);
+    assert!(diagnosis::diagnose(&bank, &unlinked).is_feasible());
+
+    // The only lab that pairs with the lecture meets at the same time
+    let linked = Plan {
+        links: vec![Link {
+            lead: course("PHY2048"),
+            follow: course("PHY2048L"),
+            rule: LinkRule::SequencePrefix { len: 2 },
+        }],
+        ..unlinked
+    };
+    let diagnosis = diagnosis::diagnose(&bank, &linked);
+    assert_eq!(diagnosis.emptied().count(), 0);
+    assert_eq!(
+        diagnosis.conflicting,
+        vec![course("PHY2048"), course("PHY2048L")]
+    );
+}
+
+#[test]
+fn a_course_too_far_from_its_only_lab_conflicts() {
+    let bank = bank_of([
+        sequenced(located(section(1, "PHY2048", 9), "Boca Raton", "SE"), "01"),
+        sequenced(located(section(2, "PHY2048L", 10), "Jupiter", "SR"), "01A"),
+    ]);
+
+    let plan = Plan {
+        links: vec![Link {
+            lead: course("PHY2048"),
+            follow: course("PHY2048L"),
+            rule: LinkRule::SequencePrefix { len: 2 },
+        }],
+        ..plan_with(&[course("PHY2048"), course("PHY2048L")])
+    };
+    assert!(diagnosis::diagnose(&bank, &plan).is_feasible());
+
+    // Ten minutes isn't enough to get to Jupiter, and the lab can't be taken without the lecture
+    let plan = Plan {
+        travel: TravelTimes {
+            campus: 60,
+            ..Default::default()
+        },
+        ..plan
+    };
+    let diagnosis = diagnosis::diagnose(&bank, &plan);
+    assert_eq!(diagnosis.emptied().count(), 0);
+    assert_eq!(
+        diagnosis.conflicting,
+        vec![course("PHY2048"), course("PHY2048L")]
+    );
+}
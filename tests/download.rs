@@ -0,0 +1,232 @@
+use reqwest::Client;
+use schedual::download::{self, DownloadOptions};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+type Handler = dyn Fn(usize, usize) -> (u16, Value) + Send + Sync;
+
+// Bare bones stand in for Banner's searchResults endpoint
+struct MockBanner {
+    endpoint: String,
+    offsets: Arc<Mutex<Vec<usize>>>,
+}
+
+impl MockBanner {
+    async fn start(handler: impl Fn(usize, usize) -> (u16, Value) + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/searchResults", listener.local_addr().unwrap());
+        let offsets = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let served = offsets.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let handler = handler.clone();
+                let served = served.clone();
+
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buffer = [0; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        let read = stream.read(&mut buffer).await.unwrap();
+                        if read == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buffer[..read]);
+                    }
+
+                    let request = String::from_utf8(request).unwrap();
+                    let offset = query_param(&request, "pageOffset");
+                    let page_size = query_param(&request, "pageMaxSize");
+                    served.lock().unwrap().push(offset);
+
+                    let (status, body) = (handler)(offset, page_size);
+                    let body = body.to_string();
+                    let response = format!(
+                        "HTTP/1.1 {status} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+
+        Self { endpoint, offsets }
+    }
+
+    fn offsets(&self) -> Vec<usize> {
+        self.offsets.lock().unwrap().clone()
+    }
+}
+
+fn query_param(request: &str, name: &str) -> usize {
+    let path = request.split_whitespace().nth(1).unwrap();
+    let query = path.split_once('?').unwrap().1;
+
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix(&format!("{name}=")))
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+fn sections(count: usize) -> Vec<Value> {
+    (0..count)
+        .map(|idx| json!({ "courseReferenceNumber": (10000 + idx).to_string() }))
+        .collect()
+}
+
+fn page(all: &[Value], offset: usize, page_size: usize) -> (u16, Value) {
+    let data = all
+        .iter()
+        .skip(offset)
+        .take(page_size)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    (
+        200,
+        json!({ "success": true, "totalCount": all.len(), "sectionsFetchedCount": all.len(), "data": data }),
+    )
+}
+
+fn options(endpoint: &str) -> DownloadOptions {
+    DownloadOptions {
+        page_size: 4,
+        delay: Duration::ZERO,
+        retries: 3,
+        backoff: Duration::from_millis(1),
+        ..DownloadOptions::new(endpoint.to_owned(), "202380".to_owned(), String::new())
+    }
+}
+
+fn output(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("schedual-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&folder);
+    std::fs::create_dir_all(&folder).unwrap();
+
+    folder.join("raw_data.json")
+}
+
+fn downloaded(output: &PathBuf) -> Vec<Value> {
+    let json: Value = serde_json::from_str(&std::fs::read_to_string(output).unwrap()).unwrap();
+    json["data"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn retries_failed_pages() {
+    let all = sections(10);
+    let expected = all.clone();
+    let attempts = Mutex::new(0);
+
+    let banner = MockBanner::start(move |offset, page_size| {
+        let mut attempts = attempts.lock().unwrap();
+        *attempts += 1;
+
+        match *attempts {
+            1 => (500, json!({})),
+            2 => (200, json!({ "success": false, "data": null })),
+            _ => page(&all, offset, page_size),
+        }
+    })
+    .await;
+
+    let output = output("retries");
+    let mut progress = Vec::new();
+    let sections = download::download(
+        &Client::new(),
+        &options(&banner.endpoint),
+        &output,
+        &mut |event| progress.push(event.to_string()),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(sections, expected);
+    assert_eq!(downloaded(&output), expected);
+    assert_eq!(banner.offsets(), vec![0, 0, 0, 4, 8]);
+    assert_eq!(
+        progress,
+        vec![
+            "Failed to pull offset 0: Got status 500 Internal Server Error, retrying in 0.0s (1/3)",
+            "Failed to pull offset 0: Banner did not report success, retrying in 0.0s (2/3)",
+            "Pulled 4",
+            "Pulled 4",
+            "Pulled 2",
+        ]
+    );
+    assert!(!download::progress_path(&output).exists());
+}
+
+#[tokio::test]
+async fn gives_up_after_retries() {
+    let banner = MockBanner::start(|_, _| (503, json!({}))).await;
+
+    let output = output("gives-up");
+    let result = download::download(
+        &Client::new(),
+        &options(&banner.endpoint),
+        &output,
+        &mut |_| {},
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(banner.offsets(), vec![0, 0, 0, 0]);
+    assert!(!output.exists());
+}
+
+#[tokio::test]
+async fn resumes_from_saved_pages() {
+    let all = sections(10);
+    let expected = all.clone();
+    let output = output("resume");
+
+    // Dies after the first page
+    let first = all.clone();
+    let broken = MockBanner::start(move |offset, page_size| {
+        if offset == 0 {
+            page(&first, offset, page_size)
+        } else {
+            (500, json!({}))
+        }
+    })
+    .await;
+
+    let result = download::download(
+        &Client::new(),
+        &options(&broken.endpoint),
+        &output,
+        &mut |_| {},
+    )
+    .await;
+    assert!(result.is_err());
+    assert!(!output.exists());
+
+    // Simulate a crash half way through saving the next page
+    let progress = download::progress_path(&output);
+    let mut saved = std::fs::read_to_string(&progress).unwrap();
+    saved.push_str(r#"{"offset":4,"total":10,"da"#);
+    std::fs::write(&progress, saved).unwrap();
+
+    let healthy = MockBanner::start(move |offset, page_size| page(&all, offset, page_size)).await;
+    let sections = download::download(
+        &Client::new(),
+        &options(&healthy.endpoint),
+        &output,
+        &mut |_| {},
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(sections, expected);
+    assert_eq!(downloaded(&output), expected);
+    assert_eq!(healthy.offsets(), vec![4, 8]);
+    assert!(!progress.exists());
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{class, date, session};
+use schedual::exclusion::{self, Exclusion};
+use schedual::{ClassBank, Days, Time};
+
+#[test]
+fn parses_rules() {
+    assert_eq!(
+        "type:Exam".parse::<Exclusion>().unwrap(),
+        Exclusion::MeetingType {
+            name: "Exam".to_owned()
+        }
+    );
+    assert_eq!(
+        "dates:2023-12-04..12/08/2023".parse::<Exclusion>().unwrap(),
+        Exclusion::Dates {
+            start: date(12, 4),
+            end: date(12, 8)
+        }
+    );
+
+    let window: Exclusion = "time:1910-2030@MW".parse().unwrap();
+    assert_eq!(
+        window,
+        Exclusion::TimeWindow {
+            start: Time::new(19, 10),
+            end: Time::new(20, 30),
+            days: Days::from_letters("MW").unwrap(),
+        }
+    );
+    assert_eq!(window.to_string().parse::<Exclusion>().unwrap(), window);
+
+    assert!("exam".parse::<Exclusion>().is_err());
+    assert!("time:1910@MW".parse::<Exclusion>().is_err());
+    assert!("time:1910-2030@MX".parse::<Exclusion>().is_err());
+}
+
+#[test]
+fn excluded_meetings_leave_the_schedule() {
+    let mut exam = session(
+        Days::from_letters("F").unwrap(),
+        Time::new(19, 10),
+        Time::new(20, 30),
+    );
+    exam.meeting_type = "Exam".to_owned();
+
+    let mut bank = ClassBank::new();
+    bank.insert(
+        1,
+        class(
+            1,
+            "PHY2048",
+            vec![
+                session(Days::mwf(), Time::new(9, 0), Time::new(9, 50)),
+                exam,
+            ],
+        ),
+    );
+    bank.insert(
+        2,
+        class(
+            2,
+            "MAC2313",
+            vec![session(Days::mwf(), Time::new(19, 0), Time::new(20, 0))],
+        ),
+    );
+
+    assert!(bank[&1].schedule.overlaps(&[&bank[&2].schedule]));
+
+    let exclusions = ["type:exam".parse().unwrap()];
+    assert_eq!(exclusion::apply(&mut bank, &exclusions), 1);
+
+    assert!(bank[&1].meetings[1].excluded);
+    assert!(!bank[&1].schedule.overlaps(&[&bank[&2].schedule]));
+
+    // Exclusions only ever add to what was already excluded
+    assert_eq!(exclusion::apply(&mut bank, &[]), 1);
+    assert!(bank[&1].meetings[1].excluded);
+}
+
+#[test]
+fn time_windows_only_match_on_their_days() {
+    let exclusion: Exclusion = "time:1900-2100@TR".parse().unwrap();
+
+    assert!(exclusion.matches(&session(
+        Days::from_letters("R").unwrap(),
+        Time::new(19, 10),
+        Time::new(20, 30)
+    )));
+    assert!(!exclusion.matches(&session(Days::mwf(), Time::new(19, 10), Time::new(20, 30))));
+    assert!(!exclusion.matches(&session(
+        Days::from_letters("T").unwrap(),
+        Time::new(18, 0),
+        Time::new(20, 30)
+    )));
+}
+
+#[test]
+fn time_windows_split_meetings_by_day() {
+    let mut bank = ClassBank::new();
+    bank.insert(
+        1,
+        class(
+            1,
+            "PHY2048",
+            vec![session(Days::mwf(), Time::new(9, 0), Time::new(9, 50))],
+        ),
+    );
+    let friday = class(
+        2,
+        "MAC2313",
+        vec![session(
+            Days::from_letters("F").unwrap(),
+            Time::new(9, 0),
+            Time::new(9, 50),
+        )],
+    );
+    let monday = class(
+        3,
+        "ENC1101",
+        vec![session(
+            Days::from_letters("M").unwrap(),
+            Time::new(9, 0),
+            Time::new(9, 50),
+        )],
+    );
+
+    let exclusions = ["time:0800-1000@F".parse().unwrap()];
+    assert_eq!(exclusion::apply(&mut bank, &exclusions), 1);
+
+    // Only Friday is left out, Monday and Wednesday still meet
+    let meetings = &bank[&1].meetings;
+    assert_eq!(meetings.len(), 2);
+    assert_eq!(meetings[0].days, Days::from_letters("MW").unwrap());
+    assert!(!meetings[0].excluded);
+    assert_eq!(meetings[1].days, Days::from_letters("F").unwrap());
+    assert!(meetings[1].excluded);
+    assert!(!bank[&1].schedule.overlaps(&[&friday.schedule]));
+    assert!(bank[&1].schedule.overlaps(&[&monday.schedule]));
+
+    // Applying again changes nothing
+    assert_eq!(exclusion::apply(&mut bank, &exclusions), 1);
+    assert_eq!(bank[&1].meetings.len(), 2);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use common::{bank_of, course, delivered, located, section, taught_by};
+use schedual::filter::{Field, Filter};
+use schedual::plan::{Plan, PlanFormat};
+use schedual::solver;
+use schedual::{Class, ClassBank, Crn};
+
+fn bank() -> ClassBank {
+    let section = |crn, campus, method, building| {
+        let class = located(section(crn, "PHY2048", 9), campus, building);
+        taught_by(delivered(class, method), &["Smith, Jane"])
+    };
+
+    bank_of([
+        section(1, "Boca Raton", "Classroom", "GS"),
+        section(2, "Boca Raton", "Fully Online", "ONL"),
+        section(3, "Jupiter", "Classroom", "SR"),
+        Class {
+            open: false,
+            ..section(4, "Boca Raton", "Classroom", "EE")
+        },
+    ])
+}
+
+fn matching(filter: &str) -> Vec<Crn> {
+    let filter: Filter = filter.parse().unwrap();
+    let mut crns = bank()
+        .values()
+        .filter(|class| filter.matches(class))
+        .map(|class| class.crn)
+        .collect::<Vec<_>>();
+    crns.sort();
+    crns
+}
+
+#[test]
+fn filters_evaluate() {
+    assert_eq!(matching(r#"campus = "boca raton""#), vec![1, 2, 4]);
+    assert_eq!(matching("instructional_method ~ online"), vec![2]);
+    assert_eq!(matching("faculty ~ smith and building = sr"), vec![3]);
+    assert_eq!(matching("not open or building = GS"), vec![1, 4]);
+    assert_eq!(
+        matching(r#"campus = "Boca Raton" and not (instructional_method ~ online or open)"#),
+        vec![4]
+    );
+    assert!(matching("room = 101").is_empty());
+}
+
+#[test]
+fn filters_round_trip_as_text() {
+    let filter = Filter::And(vec![
+        Filter::Is {
+            field: Field::Campus,
+            value: "Boca Raton".to_owned(),
+        },
+        Filter::Not(Box::new(Filter::Or(vec![
+            Filter::Has {
+                field: Field::InstructionalMethod,
+                value: "online".to_owned(),
+            },
+            Filter::Open,
+        ]))),
+        Filter::Is {
+            field: Field::SequenceNumber,
+            value: "and".to_owned(),
+        },
+    ]);
+    let text = filter.to_string();
+
+    assert_eq!(
+        text,
+        r#"campus = "Boca Raton" and not (instructional_method ~ online or open) and sequence_number = "and""#
+    );
+    assert_eq!(text.parse::<Filter>().unwrap(), filter);
+}
+
+#[test]
+fn bad_filters_are_rejected() {
+    for bad in [
+        "",
+        "campus",
+        "campus =",
+        "color = red",
+        "(open",
+        "open open",
+        r#"campus = "Boca"#,
+        "campus > 3",
+    ] {
+        assert!(bad.parse::<Filter>().is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn plans_filter_included_courses() {
+    let plan = Plan::parse(
+        r#"
+        includes = [{ Course = { subject = "PHY2048" } }]
+
+        [filters]
+        PHY2048 = 'campus = "Boca Raton" and open'
+        "#,
+        PlanFormat::Toml,
+    )
+    .unwrap();
+
+    let bank = bank();
+    let classes = solver::include_classes(&bank, &plan.includes, &plan.filters);
+    let mut crns = classes
+        .values()
+        .flatten()
+        .map(|class| class.crn)
+        .collect::<Vec<_>>();
+    crns.sort();
+    assert_eq!(crns, vec![1, 2]);
+
+    // Other courses aren't touched
+    let includes = [course("PHY2048")];
+    let mut filters = plan.filters.clone();
+    let physics = filters.remove("PHY2048").unwrap();
+    filters.insert("MAC2313".to_owned(), physics);
+    assert_eq!(
+        solver::include_classes(&bank, &includes, &filters)
+            .values()
+            .flatten()
+            .count(),
+        4
+    );
+
+    let saved = plan.to_string(PlanFormat::Toml).unwrap();
+    assert_eq!(
+        Plan::parse(&saved, PlanFormat::Toml).unwrap().filters,
+        plan.filters
+    );
+}
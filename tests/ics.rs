@@ -0,0 +1,97 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::{class, date, session, untimed};
+use schedual::{ics, Class, Days, Faculty, Time};
+
+fn physics() -> Class {
+    let mut lecture = session(Days::mwf(), Time::new(9, 0), Time::new(9, 50));
+    lecture.building_code = Some("SE".to_owned());
+    lecture.building_name = Some("Science, Engineering; Technology".to_owned());
+    lecture.room = Some(101);
+    // Starts on a Monday, so the first lab is that Thursday
+    let mut lab = session(
+        Days::from_letters("R").unwrap(),
+        Time::new(14, 0),
+        Time::new(16, 45),
+    );
+    lab.meeting_type = "Lab".to_owned();
+    let online = untimed();
+
+    Class {
+        faculty: vec![Faculty {
+            name: "Smith, Jane".to_owned(),
+            email: None,
+            primary: true,
+        }],
+        name: "General Physics with Calculus, a Very Long Title That Needs Folding".to_owned(),
+        ..class(10123, "PHY2048", vec![lecture, lab, online])
+    }
+}
+
+fn calendar(classes: &[&Class]) -> String {
+    let stamp = DateTime::from_utc(date(8, 1).and_hms_opt(12, 0, 0).unwrap(), Utc);
+    ics::calendar(classes, stamp)
+}
+
+// Undoes line folding
+fn unfold(calendar: &str) -> Vec<String> {
+    calendar
+        .replace("\r\n ", "")
+        .split("\r\n")
+        .filter(|it| !it.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn meetings_become_weekly_events() {
+    let physics = physics();
+    let lines = unfold(&calendar(&[&physics]));
+
+    assert_eq!(lines.first().unwrap(), "BEGIN:VCALENDAR");
+    assert_eq!(lines.last().unwrap(), "END:VCALENDAR");
+    // The online meeting has no set time
+    assert_eq!(lines.iter().filter(|it| *it == "BEGIN:VEVENT").count(), 2);
+
+    for line in [
+        "UID:202380-10123-0@schedual",
+        "DTSTAMP:20230801T120000Z",
+        "DTSTART:20230821T090000",
+        "DTEND:20230821T095000",
+        "RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR;UNTIL=20231208T235959",
+        // The building code wins over its name, like everywhere else
+        "LOCATION:SE 101",
+        "DESCRIPTION:CRN: 10123\\nInstructor: Smith\\, Jane\\nLocation: SE 101",
+        "UID:202380-10123-1@schedual",
+        "DTSTART:20230824T140000",
+        "DTEND:20230824T164500",
+        "RRULE:FREQ=WEEKLY;BYDAY=TH;UNTIL=20231208T235959",
+    ] {
+        assert!(lines.iter().any(|it| it == line), "{line}\n{lines:#?}");
+    }
+}
+
+#[test]
+fn lines_are_folded() {
+    let physics = physics();
+    let calendar = calendar(&[&physics]);
+
+    assert!(calendar.ends_with("\r\n"));
+    for line in calendar.split("\r\n") {
+        assert!(line.len() <= 75, "{line}");
+    }
+    assert!(calendar.contains("\r\n "));
+}
+
+#[test]
+fn meetings_outside_their_dates_are_skipped() {
+    let mut physics = physics();
+    // A single Tuesday
+    physics.meetings[0].start_date = Some(date(8, 22));
+    physics.meetings[0].end_date = Some(date(8, 22));
+    physics.meetings[1].excluded = true;
+
+    let lines = unfold(&calendar(&[&physics]));
+    assert!(!lines.iter().any(|it| it == "BEGIN:VEVENT"));
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::{bank_of, course, plan_with, schedules, section};
+use schedual::plan::Plan;
+use schedual::solver::{self, Include, Priorities};
+use schedual::ClassBank;
+
+fn bank() -> ClassBank {
+    bank_of([
+        section(1, "PHY2048", 9),
+        section(2, "HUM2020", 10),
+        section(3, "HUM2020", 10),
+        section(4, "PHI2010", 9),
+        section(5, "ARH2000", 13),
+        section(6, "LIT2010", 11),
+        section(7, "MUL2010", 9),
+    ])
+}
+
+fn plan(includes: &[Include], priorities: Priorities) -> Plan {
+    Plan {
+        priorities,
+        ..plan_with(includes)
+    }
+}
+
+fn humanities(count: usize) -> Include {
+    Include::AnyOf {
+        options: vec![course("HUM2020"), course("PHI2010"), course("ARH2000")],
+        count,
+    }
+}
+
+#[test]
+fn selections_cover_every_pick() {
+    let includes = [
+        course("PHY2048"),
+        humanities(2),
+        Include::Optional {
+            include: Box::new(course("LIT2010")),
+        },
+    ];
+    let selections = Include::selections(&includes);
+
+    assert_eq!(selections.len(), 3 * 2);
+    assert_eq!(
+        selections[0],
+        (
+            vec![
+                &includes[0],
+                &course("HUM2020"),
+                &course("PHI2010"),
+                &course("LIT2010")
+            ],
+            1
+        )
+    );
+    assert_eq!(
+        selections[1],
+        (
+            vec![&includes[0], &course("HUM2020"), &course("PHI2010")],
+            0
+        )
+    );
+    assert!(selections
+        .iter()
+        .all(|(leaves, _)| leaves.contains(&&includes[0])));
+}
+
+#[test]
+fn any_of_picks_the_sections_that_fit() {
+    // PHI2010 is at the same time as PHY2048
+    let one = schedules(
+        &bank(),
+        &plan(&[course("PHY2048"), humanities(1)], Priorities::default()),
+        10,
+    );
+    assert_eq!(one, vec![vec![1, 2], vec![1, 5]]);
+
+    let two = schedules(
+        &bank(),
+        &plan(&[course("PHY2048"), humanities(2)], Priorities::default()),
+        10,
+    );
+    assert_eq!(two, vec![vec![1, 2, 5]]);
+
+    assert!(schedules(
+        &bank(),
+        &plan(&[course("PHY2048"), humanities(3)], Priorities::default()),
+        10
+    )
+    .is_empty());
+}
+
+#[test]
+fn optional_courses_are_taken_when_they_fit_and_rewarded() {
+    let includes = [
+        course("PHY2048"),
+        Include::Optional {
+            include: Box::new(course("LIT2010")),
+        },
+        Include::Optional {
+            include: Box::new(course("MUL2010")),
+        },
+    ];
+
+    let wanted = Priorities {
+        optional_course: 10.0,
+        ..Default::default()
+    };
+    assert_eq!(
+        schedules(&bank(), &plan(&includes, wanted), 1),
+        vec![vec![1, 6]]
+    );
+
+    let unwanted = Priorities {
+        optional_course: -10.0,
+        ..Default::default()
+    };
+    assert_eq!(
+        schedules(&bank(), &plan(&includes, unwanted), 1),
+        vec![vec![1]]
+    );
+}
+
+#[test]
+fn alternatives_are_found_inside_any_of() {
+    let bank = bank();
+    let includes = [course("PHY2048"), humanities(1)];
+    let classes = solver::include_classes(&bank, &includes, &Default::default());
+
+    let schedule = vec![&bank[&1], &bank[&2]];
+    let alts = solver::find_alts(&classes, &schedule);
+
+    assert_eq!(
+        alts[1].1.iter().map(|it| it.crn).collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+}
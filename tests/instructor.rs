@@ -0,0 +1,157 @@
+mod common;
+
+use common::{bank_of, course, plan_with, schedules, section, taught_by};
+use schedual::plan::{Plan, PlanFormat};
+use schedual::solver::{Constraint, PreferredInstructor, Priorities};
+use schedual::ClassBank;
+
+fn bank() -> ClassBank {
+    bank_of([
+        taught_by(section(1, "PHY2048", 9), &["Smith, Jane"]),
+        taught_by(section(2, "PHY2048", 10), &["Doe, John"]),
+        taught_by(section(3, "PHY2048", 11), &["Smith, Jane", "Lee, Ann"]),
+        taught_by(section(4, "MAC2313", 9), &["Lee, Ann"]),
+        taught_by(section(5, "MAC2313", 13), &["Doe, John"]),
+    ])
+}
+
+#[test]
+fn instructors_include_and_exclude() {
+    let bank = bank();
+    let allowed = |constraint: &Constraint| {
+        let mut crns = bank
+            .values()
+            .filter(|class| constraint.allows(class))
+            .map(|class| class.crn)
+            .collect::<Vec<_>>();
+        crns.sort();
+        crns
+    };
+
+    let include = Constraint::Instructor {
+        course: None,
+        include: vec!["smith".to_owned()],
+        exclude: Vec::new(),
+    };
+    assert_eq!(allowed(&include), vec![1, 3]);
+
+    let exclude = Constraint::Instructor {
+        course: None,
+        include: Vec::new(),
+        exclude: vec!["Lee".to_owned()],
+    };
+    assert_eq!(allowed(&exclude), vec![1, 2, 5]);
+
+    let both = Constraint::Instructor {
+        course: None,
+        include: vec!["Smith".to_owned(), "Doe".to_owned()],
+        exclude: vec!["Lee".to_owned()],
+    };
+    assert_eq!(allowed(&both), vec![1, 2, 5]);
+    assert_eq!(
+        both.to_string(),
+        "taught by Smith or Doe, not taught by Lee"
+    );
+}
+
+#[test]
+fn instructor_constraints_only_touch_their_course() {
+    let bank = bank();
+    let constraint = Constraint::Instructor {
+        course: Some("MAC2313".to_owned()),
+        include: vec!["Doe".to_owned()],
+        exclude: Vec::new(),
+    };
+
+    let mut crns = bank
+        .values()
+        .filter(|class| constraint.allows(class))
+        .map(|class| class.crn)
+        .collect::<Vec<_>>();
+    crns.sort();
+
+    assert_eq!(crns, vec![1, 2, 3, 5]);
+    assert_eq!(constraint.to_string(), "MAC2313 taught by Doe");
+}
+
+fn plan(priorities: &Priorities, instructors: &[PreferredInstructor]) -> Plan {
+    Plan {
+        priorities: priorities.clone(),
+        instructors: instructors.to_vec(),
+        ..plan_with(&[course("PHY2048"), course("MAC2313")])
+    }
+}
+
+#[test]
+fn preferred_instructors_steer_the_search() {
+    let prefer = |course: Option<&str>, name: &str| PreferredInstructor {
+        course: course.map(|it| it.to_owned()),
+        name: name.to_owned(),
+    };
+
+    // Without a preference the tightest day wins
+    let compact = Priorities {
+        day_length: 1.0,
+        ..Default::default()
+    };
+    assert_eq!(
+        schedules(&bank(), &plan(&compact, &[prefer(None, "Doe")]), 1),
+        vec![vec![2, 4]]
+    );
+
+    let preferred = Priorities {
+        preferred_instructor: 1000.0,
+        ..compact
+    };
+    assert_eq!(
+        schedules(&bank(), &plan(&preferred, &[prefer(None, "Doe")]), 1),
+        vec![vec![2, 5]]
+    );
+    assert_eq!(
+        schedules(
+            &bank(),
+            &plan(&preferred, &[prefer(Some("PHY2048"), "Smith")]),
+            1
+        ),
+        vec![vec![3, 4]]
+    );
+}
+
+#[test]
+fn instructors_load_from_plans() {
+    let plan = Plan::parse(
+        r#"
+        constraints = [{ Instructor = { course = "PHY2048", exclude = ["Doe"] } }]
+
+        instructors = [{ name = "Smith" }, { course = "MAC2313", name = "Lee" }]
+
+        [priorities]
+        preferred_instructor = 2.0
+        "#,
+        PlanFormat::Toml,
+    )
+    .unwrap();
+
+    assert_eq!(
+        plan.constraints,
+        vec![Constraint::Instructor {
+            course: Some("PHY2048".to_owned()),
+            include: Vec::new(),
+            exclude: vec!["Doe".to_owned()],
+        }]
+    );
+    assert_eq!(plan.priorities.preferred_instructor, 2.0);
+    assert_eq!(
+        plan.instructors,
+        vec![
+            PreferredInstructor {
+                course: None,
+                name: "Smith".to_owned(),
+            },
+            PreferredInstructor {
+                course: Some("MAC2313".to_owned()),
+                name: "Lee".to_owned(),
+            },
+        ]
+    );
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::{bank_of, class, course, lecture, section, sequenced};
+use schedual::link::{self, Link, LinkPair, LinkRule};
+use schedual::plan::{Plan, PlanFormat};
+use schedual::solver;
+use schedual::{ClassBank, Crn, Days};
+
+// Both lectures meet at the same time, only their labs tell them apart
+fn bank() -> ClassBank {
+    let tuesday = Days::from_letters("T").unwrap();
+    let thursday = Days::from_letters("R").unwrap();
+
+    bank_of([
+        sequenced(section(1, "PHY2048", 9), "01"),
+        sequenced(section(2, "PHY2048", 9), "02"),
+        sequenced(class(11, "PHY2048L", vec![lecture(tuesday, 9)]), "01A"),
+        sequenced(class(12, "PHY2048L", vec![lecture(thursday, 9)]), "02A"),
+        sequenced(class(13, "PHY2048L", vec![lecture(tuesday, 9)]), "02B"),
+        // Meets during its own lecture
+        sequenced(section(14, "PHY2048L", 9), "01B"),
+    ])
+}
+
+fn physics(rule: LinkRule) -> Link {
+    Link {
+        lead: course("PHY2048"),
+        follow: course("PHY2048L"),
+        rule,
+    }
+}
+
+fn schedules(links: &[Link]) -> Vec<Vec<Crn>> {
+    let bank = bank();
+    let includes = [course("PHY2048"), course("PHY2048L")];
+
+    let classes = solver::include_classes(&bank, &includes, &Default::default());
+    let classes = solver::validate_linked_classes(classes, links, &Default::default());
+    let mut variants = solver::map_variants(&classes, &includes);
+    link::link_variants(&mut variants, &bank, links, &Default::default());
+
+    let mut found = Vec::new();
+    solver::bruteforce_schedules(
+        &variants[0].groups,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut |classes, _| {
+            let mut classes = classes.to_vec();
+            classes.sort();
+            found.push(classes);
+        },
+    );
+    found.sort();
+    found
+}
+
+#[test]
+fn unlinked_sections_pair_freely() {
+    // Sections meeting at the same time are only searched once
+    assert_eq!(schedules(&[]), vec![vec![1, 11], vec![1, 12]]);
+}
+
+#[test]
+fn sequence_prefix_pairs_sections() {
+    let links = [physics(LinkRule::SequencePrefix { len: 2 })];
+
+    assert_eq!(
+        schedules(&links),
+        vec![vec![1, 11], vec![2, 12], vec![2, 13]]
+    );
+}
+
+#[test]
+fn table_pairs_sections() {
+    let links = [physics(LinkRule::Table {
+        pairs: vec![
+            LinkPair {
+                lead: 1,
+                follow: vec![12, 14],
+            },
+            LinkPair {
+                lead: 2,
+                follow: vec![11],
+            },
+        ],
+    })];
+
+    assert_eq!(schedules(&links), vec![vec![1, 12], vec![2, 11]]);
+}
+
+#[test]
+fn links_load_from_plans() {
+    let plan = Plan::parse(
+        r#"
+        includes = [{ Course = { subject = "PHY2048" } }, { Course = { subject = "PHY2048L" } }]
+
+        [[links]]
+        lead = { Course = { subject = "PHY2048" } }
+        follow = { Course = { subject = "PHY2048L" } }
+        rule = { SequencePrefix = { len = 2 } }
+        "#,
+        PlanFormat::Toml,
+    )
+    .unwrap();
+
+    assert_eq!(
+        plan.links,
+        vec![physics(LinkRule::SequencePrefix { len: 2 })]
+    );
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::date;
+use schedual::mask::ScheduleMask;
+use schedual::{DateRange, Days, Schedule, Time};
+
+fn term() -> DateRange {
+    DateRange {
+        start: date(8, 21),
+        end: date(12, 8),
+    }
+}
+
+fn at(days: &str, start: (u8, u8), end: (u8, u8), dates: DateRange) -> Schedule {
+    Schedule::generate_dated(&[(
+        Days::from_letters(days).unwrap(),
+        Time::new(start.0, start.1),
+        Time::new(end.0, end.1),
+        dates,
+    )])
+}
+
+fn mask(days: &str, start: (u8, u8), end: (u8, u8), dates: DateRange) -> ScheduleMask {
+    ScheduleMask::new(&at(days, start, end, dates)).unwrap()
+}
+
+// Meetings on the five minute grid from a fixed seed
+fn schedules() -> Vec<Schedule> {
+    let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = |below: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed % below
+    };
+    let halves = [
+        term(),
+        DateRange {
+            start: date(8, 21),
+            end: date(10, 13),
+        },
+        DateRange {
+            start: date(10, 16),
+            end: date(12, 8),
+        },
+    ];
+
+    (0..200)
+        .map(|_| {
+            let days = ["MWF", "TR", "M", "W", "F", "S", "MW"][next(7) as usize];
+            let start = 7 * 60 + next(150) as u16 * 5;
+            let end = start + 5 * (1 + next(30) as u16);
+            let time = |minutes: u16| ((minutes / 60) as u8, (minutes % 60) as u8);
+            at(days, time(start), time(end), halves[next(3) as usize])
+        })
+        .collect()
+}
+
+#[test]
+fn masks_overlap_like_intervals() {
+    let schedules = schedules();
+    let masks = schedules
+        .iter()
+        .map(|it| ScheduleMask::new(it).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut conflicts = 0;
+    for (a, a_mask) in schedules.iter().zip(&masks) {
+        for (b, b_mask) in schedules.iter().zip(&masks) {
+            assert_eq!(a.overlaps(&[b]), a_mask.overlaps(&[b_mask]), "{a:?} {b:?}");
+            conflicts += a.overlaps(&[b]) as usize;
+        }
+    }
+    // Both kinds of pairs came up
+    assert!(conflicts > 200 && conflicts < 200 * 200);
+
+    // Off the grid these share the 9:50 slot without overlapping, so they're left to intervals
+    let early = at("M", (9, 0), (9, 52), term());
+    let late = at("M", (9, 53), (10, 40), term());
+    assert!(!early.overlaps(&[&late]));
+    assert_eq!(ScheduleMask::new(&early), None);
+    assert_eq!(ScheduleMask::new(&late), None);
+    assert_eq!(ScheduleMask::new(&at("M", (9, 3), (9, 50), term())), None);
+}
+
+#[test]
+fn touching_meetings_overlap() {
+    let nine = mask("MWF", (9, 0), (9, 50), term());
+    let ten = mask("MWF", (9, 50), (10, 40), term());
+    let later = mask("MWF", (9, 55), (10, 45), term());
+    let tuesday = mask("TR", (9, 0), (9, 50), term());
+
+    assert!(nine.overlaps(&[&ten]));
+    assert!(!nine.overlaps(&[&later]));
+    assert!(!nine.overlaps(&[&tuesday, &later]));
+    assert!(nine.overlaps(&[&tuesday, &ten]));
+    assert!(ScheduleMask::default().is_empty());
+    assert!(!ScheduleMask::default().overlaps(&[&nine]));
+}
+
+#[test]
+fn half_terms_only_overlap_on_shared_dates() {
+    let first = DateRange {
+        start: date(8, 21),
+        end: date(10, 13),
+    };
+    let second = DateRange {
+        start: date(10, 16),
+        end: date(12, 8),
+    };
+    let early = mask("MWF", (9, 0), (9, 50), first);
+    let late = mask("MWF", (9, 0), (9, 50), second);
+    let full = mask("M", (9, 30), (10, 0), term());
+
+    assert!(!early.overlaps(&[&late]));
+    assert!(early.overlaps(&[&full]));
+
+    let both = ScheduleMask::flatten(&[&early, &late]);
+    assert!(both.overlaps(&[&full]));
+    assert!(!both.overlaps(&[&mask("TR", (9, 0), (9, 50), term())]));
+}
+
+#[test]
+fn masks_convert_back_to_intervals() {
+    let schedule = Schedule::flatten(&[
+        &at("MWF", (9, 0), (9, 50), term()),
+        &at("TR", (11, 15), (12, 30), term()),
+        &at("W", (18, 0), (20, 45), term()),
+    ]);
+    let mut sorted = schedule.clone();
+    sorted.sort();
+
+    let mask = ScheduleMask::new(&schedule).unwrap();
+    assert_eq!(mask.to_schedule(), sorted);
+    assert_eq!(ScheduleMask::new(&mask.to_schedule()), Some(mask));
+}
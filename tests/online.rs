@@ -0,0 +1,72 @@
+mod common;
+
+use common::{bank_of, class, course, delivered, mwf, plan_with, schedules, section, untimed};
+use schedual::plan::{Plan, PlanFormat};
+use schedual::solver::Constraint;
+use schedual::{ClassBank, Delivery};
+
+fn bank() -> ClassBank {
+    bank_of([
+        section(1, "PHY2048", 9),
+        section(2, "MAC2313", 9),
+        delivered(class(3, "MAC2313", vec![untimed()]), "Fully Online"),
+        delivered(class(4, "MAC2313", vec![untimed()]), "Fully Online"),
+        delivered(class(5, "MAC2313", vec![mwf(9), untimed()]), "Hybrid"),
+    ])
+}
+
+fn plan(constraints: &[Constraint]) -> Plan {
+    Plan {
+        constraints: constraints.to_vec(),
+        ..plan_with(&[course("PHY2048"), course("MAC2313")])
+    }
+}
+
+#[test]
+fn delivery_from_instructional_method() {
+    let bank = bank();
+
+    assert_eq!(bank[&1].delivery(), Delivery::InPerson);
+    assert_eq!(bank[&3].delivery(), Delivery::Online);
+    assert_eq!(bank[&5].delivery(), Delivery::Hybrid);
+}
+
+#[test]
+fn sections_without_set_times_never_conflict() {
+    // The in person and hybrid sections overlap the physics lecture, the online ones only count
+    // once since they share a layout
+    assert_eq!(schedules(&bank(), &plan(&[]), 10), vec![vec![1, 3]]);
+}
+
+#[test]
+fn instructional_methods_can_be_ruled_out() {
+    let in_person = Constraint::InstructionalMethod {
+        allow: vec![Delivery::InPerson, Delivery::Hybrid],
+    };
+    assert!(schedules(&bank(), &plan(std::slice::from_ref(&in_person)), 10).is_empty());
+    assert_eq!(in_person.to_string(), "in person or hybrid sections");
+
+    let online = Constraint::InstructionalMethod {
+        allow: vec![Delivery::Online],
+    };
+    assert!(!online.allows(&bank()[&1]));
+    assert!(online.allows(&bank()[&4]));
+}
+
+#[test]
+fn instructional_methods_load_from_plans() {
+    let plan = Plan::parse(
+        r#"
+        constraints = [{ InstructionalMethod = { allow = ["InPerson", "Online"] } }]
+        "#,
+        PlanFormat::Toml,
+    )
+    .unwrap();
+
+    assert_eq!(
+        plan.constraints,
+        vec![Constraint::InstructionalMethod {
+            allow: vec![Delivery::InPerson, Delivery::Online],
+        }]
+    );
+}
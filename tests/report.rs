@@ -0,0 +1,91 @@
+mod common;
+
+use common::section;
+use schedual::report::{self, Ranked};
+use schedual::solver::Priorities;
+use schedual::Class;
+
+fn classes() -> Vec<Class> {
+    vec![
+        Class {
+            name: "Physics, \"with Calculus\"".to_owned(),
+            ..section(1, "PHY2048", 9)
+        },
+        section(2, "PHY2048", 9),
+        section(3, "MAC2313", 11),
+    ]
+}
+
+fn ranked(classes: &[Class]) -> Vec<Ranked> {
+    let alts = vec![
+        (&classes[0], vec![&classes[0], &classes[1]]),
+        (&classes[2], vec![&classes[2]]),
+    ];
+    let score = (
+        -12.5,
+        Priorities {
+            free_day: 2.0,
+            day_length: -170.0,
+            ..Default::default()
+        },
+    );
+
+    vec![Ranked::new(1, &score, &alts)]
+}
+
+#[test]
+fn json_holds_every_part() {
+    let classes = classes();
+    let json: serde_json::Value =
+        serde_json::from_str(&report::json(&ranked(&classes)).unwrap()).unwrap();
+    let schedule = &json[0];
+
+    assert_eq!(schedule["rank"], 1);
+    assert_eq!(schedule["score"], -12.5);
+    assert_eq!(schedule["credits"], serde_json::json!([6, 6]));
+    assert_eq!(schedule["breakdown"]["free_day"], 2.0);
+    assert_eq!(schedule["breakdown"]["day_length"], -170.0);
+    // Only the weighted terms
+    assert!(schedule["breakdown"].get("travel").is_none());
+
+    let physics = &schedule["classes"][0];
+    assert_eq!(physics["crn"], 1);
+    assert_eq!(physics["course"], "PHY2048");
+    assert_eq!(physics["alts"], serde_json::json!([2]));
+    assert_eq!(physics["meetings"][0]["days"], "MWF");
+    assert_eq!(physics["meetings"][0]["start"], "0900");
+    assert_eq!(physics["meetings"][0]["start_date"], "2023-08-21");
+    assert_eq!(
+        physics["meetings"][0]["description"],
+        "Class MWF 0900-0950, 2023-08-21 to 2023-12-08"
+    );
+}
+
+#[test]
+fn csv_has_a_row_per_class() {
+    let classes = classes();
+    let csv = report::csv(&ranked(&classes));
+    let lines = csv.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("rank,score,credits,similar_start_time,"));
+    assert!(lines[0].ends_with(",crn,course,title,meetings,alts"));
+    assert!(lines[1].starts_with("1,-12.5,6,"));
+    assert!(lines[1].contains(
+        r#",1,PHY2048,"Physics, ""with Calculus""","Class MWF 0900-0950, 2023-08-21 to 2023-12-08",2"#
+    ));
+    assert!(
+        lines[2].ends_with(",3,MAC2313,MAC2313,\"Class MWF 1100-1150, 2023-08-21 to 2023-12-08\",")
+    );
+}
+
+#[test]
+fn markdown_has_a_section_per_schedule() {
+    let classes = classes();
+    let markdown = report::markdown(&ranked(&classes));
+
+    assert!(markdown.starts_with("## 1. Score -12.50\n\nCredits: 6\n"));
+    assert!(markdown.contains("| free_day | 2.00 |"));
+    assert!(markdown
+        .contains("| 3 | MAC2313 | MAC2313 | Class MWF 1100-1150, 2023-08-21 to 2023-12-08 |  |"));
+}
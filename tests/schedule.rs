@@ -0,0 +1,87 @@
+use chrono::NaiveDate;
+use schedual::solver::Priorities;
+use schedual::{DateRange, Days, Schedule, Time};
+
+fn dates(start: (u32, u32), end: (u32, u32)) -> DateRange {
+    DateRange {
+        start: NaiveDate::from_ymd_opt(2023, start.0, start.1).unwrap(),
+        end: NaiveDate::from_ymd_opt(2023, end.0, end.1).unwrap(),
+    }
+}
+
+#[test]
+fn same_slot_overlaps() {
+    let a = Schedule::generate(&[(Days::mwf(), Time::new(9, 0), Time::new(9, 50))]);
+    let b = Schedule::generate(&[(Days::mwf(), Time::new(9, 30), Time::new(10, 20))]);
+
+    assert!(a.overlaps(&[&b]));
+    assert!(b.overlaps(&[&a]));
+}
+
+#[test]
+fn different_halves_of_term_do_not_overlap() {
+    let first_half = dates((8, 21), (10, 13));
+    let second_half = dates((10, 16), (12, 8));
+
+    let a =
+        Schedule::generate_dated(&[(Days::mwf(), Time::new(9, 0), Time::new(9, 50), first_half)]);
+    let b =
+        Schedule::generate_dated(&[(Days::mwf(), Time::new(9, 0), Time::new(9, 50), second_half)]);
+    let full = Schedule::generate_dated(&[(
+        Days::mwf(),
+        Time::new(9, 0),
+        Time::new(9, 50),
+        dates((8, 21), (12, 8)),
+    )]);
+
+    assert!(!a.overlaps(&[&b]));
+    assert!(!b.overlaps(&[&a]));
+    assert!(full.overlaps(&[&a]));
+    assert!(full.overlaps(&[&b]));
+}
+
+#[test]
+fn half_terms_in_the_same_slot_score() {
+    let a = Schedule::generate_dated(&[(
+        Days::mwf(),
+        Time::new(9, 0),
+        Time::new(10, 15),
+        dates((8, 21), (10, 13)),
+    )]);
+    let b = Schedule::generate_dated(&[(
+        Days::mwf(),
+        Time::new(9, 30),
+        Time::new(10, 20),
+        dates((10, 16), (12, 8)),
+    )]);
+    let mut flat = Schedule::flatten(&[&a, &b]);
+    flat.sort();
+
+    // The second block starts before the first ends, there's no time between them
+    let priorities = Priorities {
+        time_between_classes: 1.0,
+        free_block: 1.0,
+        ..Default::default()
+    };
+    let (score, breakdown) = priorities.score(&flat.data());
+    assert_eq!(score, 0.0);
+    assert_eq!(breakdown.time_between_classes, 0.0);
+}
+
+#[test]
+fn sharing_a_single_day_overlaps() {
+    let a = Schedule::generate_dated(&[(
+        Days::mwf(),
+        Time::new(9, 0),
+        Time::new(9, 50),
+        dates((8, 21), (10, 13)),
+    )]);
+    let b = Schedule::generate_dated(&[(
+        Days::mwf(),
+        Time::new(9, 0),
+        Time::new(9, 50),
+        dates((10, 13), (12, 8)),
+    )]);
+
+    assert!(a.overlaps(&[&b]));
+}
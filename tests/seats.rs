@@ -0,0 +1,201 @@
+mod common;
+
+use common::{bank_of, best, course, plan_with, section};
+use schedual::plan::{Plan, PlanFormat};
+use schedual::solver::{self, Constraint, Include, Picks, Priorities};
+use schedual::{Class, ClassBank, Crn, CrossList, Enrollment, Schedule};
+
+fn seats(count: u64, capacity: u64) -> Enrollment {
+    Enrollment {
+        count,
+        capacity,
+        available: capacity as i64 - count as i64,
+    }
+}
+
+fn with_seats(class: Class, enrollment: Enrollment) -> Class {
+    Class {
+        open: enrollment.available > 0,
+        enrollment,
+        ..class
+    }
+}
+
+fn wait_listed(crn: Crn, ahead: u64, capacity: u64) -> Class {
+    Class {
+        wait_list: seats(ahead, capacity),
+        ..with_seats(section(crn, "PHY2048", 9), seats(30, 30))
+    }
+}
+
+#[test]
+fn open_sections_need_seats() {
+    let open = with_seats(section(1, "PHY2048", 9), seats(10, 30));
+    let full = with_seats(section(2, "PHY2048", 9), seats(30, 30));
+    let closed = Class {
+        open: false,
+        ..open.clone()
+    };
+
+    assert!(Constraint::Open.allows(&open));
+    assert!(!Constraint::Open.allows(&full));
+    assert!(!Constraint::Open.allows(&closed));
+}
+
+#[test]
+fn wait_lists_with_room_and_few_ahead() {
+    let constraint = Constraint::WaitList { max_ahead: 3 };
+
+    assert!(constraint.allows(&with_seats(section(1, "PHY2048", 9), seats(10, 30))));
+    assert!(constraint.allows(&wait_listed(2, 3, 10)));
+    assert!(!constraint.allows(&wait_listed(3, 4, 10)));
+    // No room left on the wait list
+    assert!(!constraint.allows(&wait_listed(4, 2, 2)));
+}
+
+#[test]
+fn cross_lists_need_seats() {
+    let cross_listed = |available| Class {
+        cross_list: Some(CrossList {
+            cross_list: 1,
+            cross_list_available: available,
+            cross_list_capacity: 40,
+            cross_list_count: (40 - available) as u64,
+        }),
+        ..with_seats(section(1, "PHY2048", 9), seats(10, 30))
+    };
+
+    assert!(Constraint::CrossListSeats.allows(&with_seats(section(2, "PHY2048", 9), seats(30, 30))));
+    assert!(Constraint::CrossListSeats.allows(&cross_listed(4)));
+    assert!(!Constraint::CrossListSeats.allows(&cross_listed(0)));
+
+    assert_eq!(
+        with_seats(section(2, "PHY2048", 9), seats(10, 40)).free_seats(),
+        75
+    );
+    assert_eq!(cross_listed(4).free_seats(), 10);
+    assert_eq!(
+        with_seats(section(3, "PHY2048", 9), seats(0, 0)).free_seats(),
+        0
+    );
+}
+
+fn bank() -> ClassBank {
+    bank_of([
+        with_seats(section(1, "PHY2048", 9), seats(29, 30)),
+        with_seats(section(2, "PHY2048", 10), seats(5, 30)),
+        with_seats(section(3, "PHY2048", 11), seats(15, 30)),
+        with_seats(section(4, "MAC2313", 10), seats(0, 30)),
+        with_seats(section(5, "MAC2313", 12), seats(20, 30)),
+        with_seats(section(6, "MAC2313", 9), seats(30, 30)),
+        with_seats(section(7, "ENC1101", 11), seats(12, 20)),
+        with_seats(section(8, "ENC1101", 13), seats(2, 20)),
+        with_seats(section(9, "ENC1101", 14), seats(19, 20)),
+    ])
+}
+
+fn includes() -> Vec<Include> {
+    vec![course("PHY2048"), course("MAC2313"), course("ENC1101")]
+}
+
+#[test]
+fn free_seats_are_preferred() {
+    let priorities = Priorities {
+        free_seats: 1.0,
+        ..Default::default()
+    };
+    let plan = Plan {
+        priorities,
+        ..plan_with(&includes())
+    };
+    let (score, crns) = best(&bank(), &plan, 1).remove(0);
+
+    // The emptiest sections of each course, 2 and 4 would overlap
+    assert_eq!(crns, vec![3, 4, 8]);
+    assert_eq!(score, (50.0 + 100.0 + 90.0) / 3.0);
+
+    let fullest = Priorities {
+        free_seats: -1.0,
+        ..Default::default()
+    };
+    let plan = Plan {
+        priorities: fullest,
+        ..plan
+    };
+    assert_eq!(best(&bank(), &plan, 1)[0].1, vec![1, 5, 9]);
+}
+
+#[test]
+fn free_seats_match_exhaustive_scoring() {
+    let priorities = Priorities {
+        free_seats: 2.0,
+        day_length: 0.5,
+        free_day: 30.0,
+        ..Default::default()
+    };
+
+    let bank = bank();
+    let includes = includes();
+    let classes = solver::include_classes(&bank, &includes, &Default::default());
+    let variants = solver::map_variants(&classes, &includes);
+    let data = &variants[0].groups;
+
+    let mut exhaustive = Vec::new();
+    solver::bruteforce_schedules(
+        data,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut |crns, schedule| {
+            let mut flat = Schedule::flatten(schedule);
+            flat.sort();
+
+            let free_seats =
+                crns.iter().map(|crn| bank[crn].free_seats()).sum::<u32>() as f64 / 3.0;
+            let picks = Picks {
+                free_seats,
+                ..Default::default()
+            };
+            let (score, _) = priorities.score_picks(&flat.data(), &picks);
+            exhaustive.push((score, crns.to_vec()));
+        },
+    );
+    // Stable, so ties stay in search order
+    exhaustive.sort_by(|a, b| b.0.total_cmp(&a.0));
+    exhaustive.truncate(5);
+
+    let plan = Plan {
+        priorities,
+        ..plan_with(&includes)
+    };
+    assert_eq!(best(&bank, &plan, 5), exhaustive);
+}
+
+#[test]
+fn seat_constraints_load_from_plans() {
+    let plan = Plan::parse(
+        r#"
+        constraints = ["Open", { WaitList = { max_ahead = 3 } }, "CrossListSeats"]
+
+        [priorities]
+        free_seats = 1.5
+        "#,
+        PlanFormat::Toml,
+    )
+    .unwrap();
+
+    assert_eq!(
+        plan.constraints,
+        vec![
+            Constraint::Open,
+            Constraint::WaitList { max_ahead: 3 },
+            Constraint::CrossListSeats
+        ]
+    );
+    assert_eq!(plan.priorities.free_seats, 1.5);
+
+    let saved = plan.to_string(PlanFormat::Toml).unwrap();
+    assert_eq!(
+        Plan::parse(&saved, PlanFormat::Toml).unwrap().constraints,
+        plan.constraints
+    );
+}
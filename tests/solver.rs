@@ -0,0 +1,343 @@
+mod common;
+
+use common::date;
+use schedual::solver::{self, Picks, Priorities, Schedules};
+use schedual::{DateRange, Days, Schedule, SmallClass, Time};
+
+// Small deterministic generator so failures can be replayed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, below: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % below
+    }
+}
+
+fn random_groups(rng: &mut Rng, groups: usize, sections: usize) -> Vec<Vec<SmallClass>> {
+    let day_sets = [
+        Days::mwf(),
+        Days::ttf(),
+        Days::from_letters("MW").unwrap(),
+        Days::from_letters("TR").unwrap(),
+        Days::from_letters("F").unwrap(),
+        Days::from_letters("S").unwrap(),
+        Days::never(),
+    ];
+    let halves = [
+        DateRange::ALWAYS,
+        DateRange {
+            start: date(8, 21),
+            end: date(10, 13),
+        },
+        DateRange {
+            start: date(10, 16),
+            end: date(12, 8),
+        },
+    ];
+
+    let mut crn = 10000;
+    (0..groups)
+        .map(|_| {
+            (0..sections)
+                .map(|_| {
+                    let meetings = (0..1 + rng.next(2))
+                        .map(|_| {
+                            let start = 8 * 60 + rng.next(24) as u16 * 30;
+                            let length = [50, 75, 110, 170][rng.next(4) as usize];
+                            (
+                                day_sets[rng.next(day_sets.len() as u64) as usize],
+                                Time::new((start / 60) as u8, (start % 60) as u8),
+                                Time::new(
+                                    ((start + length) / 60) as u8,
+                                    ((start + length) % 60) as u8,
+                                ),
+                                halves[rng.next(halves.len() as u64) as usize],
+                            )
+                        })
+                        .collect::<Vec<_>>();
+
+                    crn += 1;
+                    SmallClass {
+                        crn,
+                        linked: Vec::new(),
+                        credits: (3, 3),
+                        free_seats: 0,
+                        preferred: 0,
+                        schedule: Schedule::generate_dated(&meetings),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn exhaustive(
+    data: &[Vec<SmallClass>],
+    priorities: &Priorities,
+    count: usize,
+) -> Vec<(f64, Vec<u64>)> {
+    let mut solutions = Vec::new();
+    solver::bruteforce_schedules(
+        data,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut |classes, times| {
+            let mut schedule = Schedule::flatten(times);
+            schedule.sort();
+            solutions.push((priorities.score(&schedule.data()).0, classes.to_vec()));
+        },
+    );
+
+    solutions.sort_by(|(a, _), (b, _)| f64::total_cmp(a, b).reverse());
+    solutions.truncate(count);
+    solutions
+}
+
+fn priority_sets() -> Vec<Priorities> {
+    vec![
+        Priorities {
+            time_between_classes: 0.4,
+            similar_start_time: 0.5,
+            similar_end_time: 0.1,
+            free_block: 0.0,
+            free_day: 2.0,
+            day_length: 0.5,
+            optional_course: 0.0,
+            free_seats: 0.0,
+            ..Default::default()
+        },
+        Priorities {
+            free_day: 50.0,
+            ..Default::default()
+        },
+        Priorities {
+            similar_start_time: -0.3,
+            similar_end_time: 1.0,
+            time_between_classes: -2.0,
+            free_block: 1.5,
+            free_day: -1.0,
+            day_length: -0.2,
+            optional_course: 0.0,
+            free_seats: 0.0,
+            ..Default::default()
+        },
+        // Every schedule ties, so the order they were found in decides
+        Priorities::default(),
+    ]
+}
+
+#[test]
+fn top_schedules_match_exhaustive_search() {
+    let mut rng = Rng(0x5eed);
+
+    for round in 0..40 {
+        let data = random_groups(&mut rng, 2 + round % 4, 3 + round % 5);
+
+        for priorities in priority_sets() {
+            for count in [0, 1, 3, 10] {
+                let expected = exhaustive(&data, &priorities, count);
+                let found = solver::top_schedules(&data, &priorities, count)
+                    .into_iter()
+                    .map(|((score, _), classes)| (score, classes))
+                    .collect::<Vec<_>>();
+
+                assert_eq!(
+                    found, expected,
+                    "round {round}, {priorities:?}, top {count}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn parallel_search_matches_serial_search() {
+    let mut rng = Rng(0xfeed);
+
+    for round in 0..20 {
+        // Few sections per group so the search gets split on the first two groups as well
+        let data = random_groups(&mut rng, 2 + round % 4, 2 + round % 7);
+
+        for priorities in priority_sets() {
+            for count in [1, 3, 25] {
+                let serial = solver::top_schedules(&data, &priorities, count);
+
+                for threads in [1, 2, 3, 8] {
+                    let parallel =
+                        solver::top_schedules_parallel(&data, &priorities, count, threads);
+
+                    assert_eq!(
+                        parallel
+                            .iter()
+                            .map(|(score, classes)| (score.0, classes))
+                            .collect::<Vec<_>>(),
+                        serial
+                            .iter()
+                            .map(|(score, classes)| (score.0, classes))
+                            .collect::<Vec<_>>(),
+                        "round {round}, {priorities:?}, top {count}, {threads} threads"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn schedules_iterator_matches_bruteforce() {
+    let mut rng = Rng(0xcafe);
+
+    for round in 0..30 {
+        let data = random_groups(&mut rng, round % 5, 1 + round % 6);
+
+        let mut expected = Vec::new();
+        solver::bruteforce_schedules(
+            &data,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut |classes, times| {
+                expected.push((classes.to_vec(), times.to_vec()));
+            },
+        );
+
+        assert_eq!(
+            Schedules::new(&data).collect::<Vec<_>>(),
+            expected,
+            "round {round}"
+        );
+    }
+}
+
+#[test]
+fn schedules_iterator_resumes_where_it_stopped() {
+    let mut rng = Rng(0xbeef);
+    let data = random_groups(&mut rng, 4, 5);
+    let all = Schedules::new(&data)
+        .map(|(classes, _)| classes)
+        .collect::<Vec<_>>();
+    assert!(all.len() > 10);
+
+    let mut pages = Vec::new();
+    let mut schedules = Schedules::new(&data);
+    loop {
+        let page = schedules
+            .by_ref()
+            .take(7)
+            .map(|(classes, _)| classes)
+            .collect::<Vec<_>>();
+        let last = page.len() < 7;
+        pages.extend(page);
+        if last {
+            break;
+        }
+
+        // Start over from the saved position like a fresh session would
+        let path = schedules.path().unwrap().to_vec();
+        schedules = Schedules::resume(&data, &path).unwrap();
+    }
+
+    assert_eq!(pages, all);
+    assert_eq!(Schedules::new(&[]).next(), None);
+
+    // Paths that don't fit the groups any more
+    let mut schedules = Schedules::new(&data);
+    schedules.next();
+    let path = schedules.path().unwrap().to_vec();
+    assert!(Schedules::resume(&data, &path).is_some());
+    assert!(Schedules::resume(&data, &path[1..]).is_none());
+    let mut stale = path.clone();
+    stale[0] = data[0].len();
+    assert!(Schedules::resume(&data, &stale).is_none());
+    assert!(Schedules::resume(&[], &[]).is_none());
+}
+
+#[test]
+fn propagation_finds_the_same_schedules() {
+    let mut rng = Rng(0xf00d);
+
+    for round in 0..30 {
+        let data = random_groups(&mut rng, round % 6, 1 + round % 7);
+
+        let mut expected = Vec::new();
+        let brute =
+            solver::bruteforce_stats(&data, &mut |classes, _| expected.push(classes.to_vec()));
+        let mut found = Vec::new();
+        let propagated =
+            solver::propagate_schedules(&data, &mut |classes, _| found.push(classes.to_vec()));
+
+        expected.sort();
+        found.sort();
+        assert_eq!(found, expected, "round {round}");
+        assert_eq!(brute.solutions, expected.len() as u64);
+        assert_eq!(propagated.solutions, expected.len() as u64);
+    }
+}
+
+#[test]
+fn pruning_conflicts_keeps_the_best_schedules() {
+    let mut rng = Rng(0xd1ce);
+    let priorities = &priority_sets()[0];
+
+    for round in 0..20 {
+        let data = random_groups(&mut rng, 2 + round % 4, 2 + round % 6);
+        let pruned = solver::prune_conflicts(data.clone());
+
+        assert!(pruned.iter().flatten().count() <= data.iter().flatten().count());
+        assert_eq!(
+            solver::top_schedules(&pruned, priorities, 5)
+                .into_iter()
+                .map(|((score, _), classes)| (score, classes))
+                .collect::<Vec<_>>(),
+            exhaustive(&data, priorities, 5),
+            "round {round}"
+        );
+    }
+}
+
+#[test]
+fn upper_bound_covers_every_completion() {
+    let mut rng = Rng(0xb0b);
+
+    for _ in 0..40 {
+        let data = random_groups(&mut rng, 4, 4);
+        let rest = solver::Envelope::suffixes(&data);
+
+        for priorities in priority_sets() {
+            let none = Picks::default();
+            for first in &data[0] {
+                let bound = priorities.upper_bound(&[&first.schedule], &rest[1], &none, &none);
+
+                solver::bruteforce_schedules(
+                    &data[1..],
+                    &mut Vec::new(),
+                    &mut vec![&first.schedule],
+                    &mut |_, times| {
+                        let mut schedule = Schedule::flatten(times);
+                        schedule.sort();
+                        let score = priorities.score(&schedule.data()).0;
+                        assert!(score <= bound, "{score} > {bound} for {priorities:?}");
+                    },
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn schedule_without_meetings_scores_like_an_empty_week() {
+    let priorities = &priority_sets()[0];
+    let empty = Schedule::default();
+
+    assert_eq!(
+        priorities.upper_bound(
+            &[&empty],
+            &solver::Envelope::suffixes(&[])[0],
+            &Picks::default(),
+            &Picks::default()
+        ),
+        priorities.score(&empty.data()).0
+    );
+}
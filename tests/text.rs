@@ -0,0 +1,148 @@
+mod common;
+
+use common::{class, date, session, untimed};
+use schedual::text::{self, Resolution};
+use schedual::{Class, Days, Session, Time};
+
+fn classes() -> Vec<Class> {
+    let first_half = Session {
+        end_date: Some(date(10, 13)),
+        ..session(Days::mwf(), Time::new(9, 50), Time::new(10, 40))
+    };
+    let second_half = Session {
+        start_date: Some(date(10, 16)),
+        ..session(Days::mwf(), Time::new(9, 50), Time::new(10, 40))
+    };
+    let online = untimed();
+
+    vec![
+        class(1, "PHY2048", vec![first_half]),
+        class(2, "PHY2049", vec![second_half]),
+        class(
+            3,
+            "MAC2313",
+            vec![session(
+                Days::from_letters("TR").unwrap(),
+                Time::new(11, 15),
+                Time::new(12, 30),
+            )],
+        ),
+        Class {
+            instructional_method: "Fully Online".to_owned(),
+            ..class(4, "ENC1101", vec![online])
+        },
+    ]
+}
+
+fn render(classes: &[Class], minutes: u8) -> String {
+    let alt = class(5, "MAC2313", Vec::new());
+    let mut alts = classes
+        .iter()
+        .map(|class| (class, vec![class]))
+        .collect::<Vec<_>>();
+    if let Some((_, alts)) = alts.iter_mut().find(|(class, _)| class.crn == 3) {
+        alts.push(&alt);
+    }
+
+    text::timetable(&alts, Resolution::new(minutes).unwrap())
+}
+
+// Cells of the row starting at `time`
+fn row(text: &str, time: &str) -> Vec<String> {
+    let line = text
+        .lines()
+        .find(|line| line.starts_with(&format!("| {time} ")))
+        .unwrap_or_else(|| panic!("No {time} row in\n{text}"));
+    line.split('|')
+        .map(|cell| cell.trim().to_owned())
+        .filter(|cell| !cell.is_empty())
+        .collect()
+}
+
+#[test]
+fn resolutions_are_checked() {
+    assert_eq!("15".parse::<Resolution>().unwrap().minutes(), 15);
+    assert_eq!(Resolution::default().minutes(), 30);
+    for bad in ["0", "7", "20", "60", "half"] {
+        assert!(bad.parse::<Resolution>().is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn meetings_keep_their_exact_times() {
+    let classes = classes();
+    let text = render(&classes, 30);
+
+    // 9:50 lands in the 9:30 row rather than being rounded to 10:00
+    assert_eq!(row(&text, "09:30")[1], "PHY2048 1");
+    assert_eq!(row(&text, "10:00")[1], "09:50-10:40");
+    assert_eq!(row(&text, "11:00")[2], "MAC2313 3 (5)");
+    assert_eq!(row(&text, "11:30")[2], "11:15-12:30");
+    assert!(!text.contains("| 12:30 "));
+
+    let fine = render(&classes, 5);
+    assert_eq!(row(&fine, "09:50")[1], "PHY2048 1");
+    assert_eq!(row(&fine, "09:55")[1], "09:50-10:40");
+    assert_eq!(row(&fine, "10:35")[1], "·");
+    assert_eq!(row(&fine, "10:40").len(), 1);
+}
+
+#[test]
+fn half_term_classes_get_their_own_column() {
+    let classes = classes();
+    let text = render(&classes, 15);
+    let header = text.lines().nth(1).unwrap();
+
+    assert!(header.contains("Monday"));
+    assert!(header.contains("Monday 2"));
+    assert!(header.contains("Wednesday 2"));
+    assert!(!header.contains("Tuesday 2"));
+    assert!(!header.contains("Sunday"));
+
+    let row = row(&text, "10:15");
+    assert_eq!(row[1], "08/21-10/13");
+    assert_eq!(row[2], "10/16-12/08");
+}
+
+#[test]
+fn sections_without_times_are_listed_below() {
+    let classes = classes();
+    let text = render(&classes, 30);
+
+    assert!(text
+        .trim_end()
+        .ends_with("No set time: ENC1101 4, Fully Online Class"));
+    assert_eq!(
+        render(&classes[3..], 30),
+        "No set time: ENC1101 4, Fully Online Class\n"
+    );
+    assert_eq!(render(&[], 30), "No classes\n");
+}
+
+#[test]
+fn meetings_sharing_a_row_get_their_own_column() {
+    let tuesday = |start: (u8, u8), end: (u8, u8)| {
+        session(
+            Days::from_letters("T").unwrap(),
+            Time::new(start.0, start.1),
+            Time::new(end.0, end.1),
+        )
+    };
+    let classes = vec![
+        class(1, "PHY2048", vec![tuesday((10, 0), (10, 15))]),
+        class(2, "MAC2313", vec![tuesday((10, 20), (11, 10))]),
+    ];
+
+    // Both land in the 10:00 row
+    let text = render(&classes, 30);
+    let header = text.lines().nth(1).unwrap();
+    assert!(header.contains("Tuesday 2"));
+    assert_eq!(row(&text, "10:00")[1..], ["PHY2048 1", "MAC2313 2"]);
+    assert_eq!(row(&text, "10:30")[1..], ["10:20-11:10"]);
+
+    // Rows fine enough to keep them apart need one column
+    let fine = render(&classes, 5);
+    assert!(!fine.lines().nth(1).unwrap().contains("Tuesday 2"));
+    assert_eq!(row(&fine, "10:00")[1..], ["PHY2048 1"]);
+    assert_eq!(row(&fine, "10:20")[1..], ["MAC2313 2"]);
+}
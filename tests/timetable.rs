@@ -0,0 +1,131 @@
+mod common;
+
+use common::{class, date, mwf, session, untimed};
+use schedual::solver::Priorities;
+use schedual::timetable::Timetable;
+use schedual::{html, Class, Day, Days, Faculty, Session, Time};
+
+// Two half term sections at the same time and a full term one overlapping both
+fn classes() -> Vec<Class> {
+    let first_half = Session {
+        end_date: Some(date(10, 13)),
+        ..mwf(9)
+    };
+    let second_half = Session {
+        start_date: Some(date(10, 16)),
+        ..mwf(9)
+    };
+    let long = session(
+        Days::from_letters("M").unwrap(),
+        Time::new(9, 30),
+        Time::new(11, 0),
+    );
+    let online = untimed();
+
+    vec![
+        class(1, "PHY2048", vec![first_half]),
+        class(2, "PHY2049", vec![second_half]),
+        class(3, "CHM2045", vec![long]),
+        class(4, "ENC1101", vec![mwf(13)]),
+        class(5, "AMH2020", vec![online]),
+    ]
+}
+
+#[test]
+fn overlapping_meetings_share_their_day() {
+    let classes = classes();
+    let timetable = Timetable::new(&classes.iter().collect::<Vec<_>>());
+
+    assert_eq!(timetable.hours, Some((9, 14)));
+    assert_eq!(timetable.hour_lines().count(), 6);
+    assert_eq!(timetable.untimed.len(), 1);
+    assert_eq!(timetable.untimed[0].crn, 5);
+
+    let monday = timetable
+        .blocks
+        .iter()
+        .filter(|it| it.day == Day::Monday)
+        .map(|it| (it.class.crn, it.lane, it.lanes))
+        .collect::<Vec<_>>();
+    assert_eq!(monday, vec![(1, 0, 3), (2, 1, 3), (3, 2, 3), (4, 0, 1)]);
+
+    let wednesday = timetable
+        .blocks
+        .iter()
+        .filter(|it| it.day == Day::Wednesday)
+        .map(|it| (it.class.crn, it.lane, it.lanes))
+        .collect::<Vec<_>>();
+    assert_eq!(wednesday, vec![(1, 0, 2), (2, 1, 2), (4, 0, 1)]);
+}
+
+#[test]
+fn blocks_are_placed_as_fractions_of_the_grid() {
+    let classes = classes();
+    let timetable = Timetable::new(&classes.iter().collect::<Vec<_>>());
+
+    assert_eq!(timetable.y(Time::new(9, 0)), 0.0);
+    assert_eq!(timetable.y(Time::new(14, 0)), 1.0);
+
+    let english = timetable
+        .blocks
+        .iter()
+        .find(|it| it.class.crn == 4 && it.day == Day::Friday)
+        .unwrap();
+    let [left, top, right, bottom] = english.rect(&timetable);
+    assert_eq!(left, 5.0 / 7.0);
+    assert_eq!(right, 6.0 / 7.0);
+    assert_eq!(top, 0.8);
+    assert!((bottom - (4.0 + 50.0 / 60.0) / 5.0).abs() < 1e-6);
+
+    // Side by side within the column
+    let second_half = timetable
+        .blocks
+        .iter()
+        .find(|it| it.class.crn == 2 && it.day == Day::Monday)
+        .unwrap();
+    let [left, _, right, _] = second_half.rect(&timetable);
+    assert!((left - (1.0 + 1.0 / 3.0) / 7.0).abs() < 1e-6);
+    assert!((right - (1.0 + 2.0 / 3.0) / 7.0).abs() < 1e-6);
+}
+
+#[test]
+fn html_pages_show_every_schedule() {
+    let mut classes = classes();
+    classes[0].name = "Physics <Honors> & Lab".to_owned();
+    classes[0].faculty = vec![Faculty {
+        name: "Smith, Jane".to_owned(),
+        email: None,
+        primary: true,
+    }];
+    classes[0].meetings[0].building_code = Some("SE".to_owned());
+    classes[0].meetings[0].room = Some(101);
+    let alt = class(6, "PHY2048", vec![mwf(9)]);
+
+    let score = (
+        4.5,
+        Priorities {
+            free_day: 2.0,
+            ..Default::default()
+        },
+    );
+    let mut shown = classes
+        .iter()
+        .map(|class| (class, vec![class]))
+        .collect::<Vec<_>>();
+    shown[0].1.push(&alt);
+    let page = html::page("Fall", &[(&score, shown.clone()), (&score, shown)]);
+
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.trim_end().ends_with("</html>"));
+    assert_eq!(page.matches("<section>").count(), 2);
+    assert!(page.contains("<h2>1. Score 4.50</h2>"));
+    assert!(page.contains("<h2>2. Score 4.50</h2>"));
+    assert!(page.contains("<p>Credits: 15</p>"));
+    assert!(page.contains("Physics &lt;Honors&gt; &amp; Lab"));
+    assert!(page.contains("<text x=\"3\" y=\"38\" fill="));
+    assert!(page.contains(">SE 101</text>"));
+    assert!(page.contains(">Smith, Jane</text>"));
+    assert!(page.contains("<td>free_day</td><td class=\"number\">2.00</td>"));
+    assert!(page.contains("<td>6</td></tr>"));
+    assert!(page.contains("<p>No set time: AMH2020 5</p>"));
+}
@@ -0,0 +1,234 @@
+mod common;
+
+use common::{bank_of, best, course, located, plan_with, section, sequenced};
+use schedual::link::{self, Link, LinkRule};
+use schedual::plan::{Plan, PlanFormat};
+use schedual::solver::{self, Include, Picks, Priorities};
+use schedual::travel::{Travel, TravelTime, TravelTimes};
+use schedual::{ClassBank, Place, Schedule};
+
+fn times() -> TravelTimes {
+    TravelTimes {
+        building: 10,
+        campus: 60,
+        times: vec![
+            TravelTime {
+                from: "GS".to_owned(),
+                to: "EE".to_owned(),
+                minutes: 20,
+            },
+            TravelTime {
+                from: "Jupiter".to_owned(),
+                to: "Boca Raton".to_owned(),
+                minutes: 45,
+            },
+        ],
+    }
+}
+
+#[test]
+fn travel_times_between_places() {
+    let travel = Travel::new(&times());
+    let boca = |building| Place::of("Boca Raton", Some(building));
+
+    assert_eq!(travel.minutes(boca("GS"), boca("GS")), 0);
+    assert_eq!(travel.minutes(boca("GS"), boca("SE")), 10);
+    assert_eq!(travel.minutes(boca("ee"), boca("GS")), 20);
+    assert_eq!(
+        travel.minutes(boca("GS"), Place::of("Jupiter", Some("SR"))),
+        45
+    );
+    assert_eq!(
+        travel.minutes(boca("GS"), Place::of("Davie", Some("LA"))),
+        60
+    );
+    // Online and unknown places are never far
+    assert_eq!(travel.minutes(boca("GS"), Place::of("Boca Raton", None)), 0);
+    assert_eq!(travel.minutes(boca("GS"), Place::NONE), 0);
+}
+
+#[test]
+fn back_to_back_meetings_need_time_to_travel() {
+    let travel = Travel::new(&times());
+    let nine = located(section(1, "PHY2048", 9), "Boca Raton", "GS");
+    let ten = |building| located(section(2, "MAC2313", 10), "Boca Raton", building);
+
+    // 10 minutes between the two
+    assert!(!travel.conflicts(&nine.schedule, &[&ten("GS").schedule]));
+    assert!(!travel.conflicts(&nine.schedule, &[&ten("SE").schedule]));
+    assert!(travel.conflicts(&nine.schedule, &[&ten("EE").schedule]));
+    assert!(travel.conflicts(&ten("EE").schedule, &[&nine.schedule]));
+    assert!(!Travel::default().conflicts(&nine.schedule, &[&ten("EE").schedule]));
+}
+
+fn bank() -> ClassBank {
+    bank_of([
+        located(section(1, "PHY2048", 9), "Boca Raton", "GS"),
+        located(section(2, "PHY2048", 12), "Boca Raton", "GS"),
+        located(section(3, "MAC2313", 10), "Boca Raton", "EE"),
+        located(section(4, "MAC2313", 10), "Boca Raton", "SE"),
+        located(section(5, "MAC2313", 11), "Jupiter", "SR"),
+        located(section(6, "MAC2313", 14), "Boca Raton", "GS"),
+        located(section(7, "ENC1101", 13), "Boca Raton", "SE"),
+        located(section(8, "ENC1101", 15), "Jupiter", "SR"),
+    ])
+}
+
+fn includes() -> Vec<Include> {
+    vec![course("PHY2048"), course("MAC2313"), course("ENC1101")]
+}
+
+fn plan(priorities: Priorities) -> Plan {
+    Plan {
+        priorities,
+        travel: times(),
+        ..plan_with(&includes())
+    }
+}
+
+#[test]
+fn sections_in_other_places_stay_apart() {
+    let bank = bank();
+    let includes = includes();
+    let count = |travel: &TravelTimes| {
+        let classes = solver::include_classes(&bank, &includes, &Default::default());
+        solver::validate_linked_classes(classes, &[], travel)[&includes[1]].len()
+    };
+
+    // Sections 3 and 4 meet at the same time
+    assert_eq!(count(&TravelTimes::default()), 3);
+    assert_eq!(count(&times()), 4);
+}
+
+#[test]
+fn search_skips_schedules_without_time_to_travel() {
+    let bank = bank();
+    let travel = Travel::new(&times());
+    let found = best(&bank, &plan(Priorities::default()), 100);
+    assert!(!found.is_empty());
+
+    for (_, crns) in found {
+        let schedules = crns
+            .iter()
+            .map(|crn| &bank[crn].schedule)
+            .collect::<Vec<_>>();
+        for (idx, schedule) in schedules.iter().enumerate() {
+            assert!(
+                !travel.conflicts(schedule, &schedules[idx + 1..]),
+                "{crns:?}"
+            );
+        }
+
+        // 10 minutes from Jupiter to physics at 12, and from physics at 9 over to EE
+        assert!(!(crns.contains(&5) && crns.contains(&2)), "{crns:?}");
+        assert!(!(crns.contains(&1) && crns.contains(&3)), "{crns:?}");
+    }
+}
+
+#[test]
+fn linked_sections_need_time_to_travel() {
+    let bank = bank_of([
+        sequenced(located(section(1, "PHY2048", 9), "Boca Raton", "GS"), "01"),
+        // Lab right after the lecture on another campus
+        sequenced(located(section(2, "PHY2048L", 10), "Jupiter", "SR"), "01A"),
+        sequenced(
+            located(section(3, "PHY2048L", 10), "Boca Raton", "GS"),
+            "01B",
+        ),
+    ]);
+    let includes = [course("PHY2048"), course("PHY2048L")];
+    let links = [Link {
+        lead: course("PHY2048"),
+        follow: course("PHY2048L"),
+        rule: LinkRule::SequencePrefix { len: 2 },
+    }];
+
+    let pairs = |travel: &TravelTimes| {
+        let classes = solver::include_classes(&bank, &includes, &Default::default());
+        let classes = solver::validate_linked_classes(classes, &links, travel);
+        let mut variants = solver::map_variants(&classes, &includes);
+        link::link_variants(&mut variants, &bank, &links, travel);
+
+        let mut pairs = variants[0].groups[0]
+            .iter()
+            .map(|it| it.crns().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        pairs.sort();
+        pairs
+    };
+
+    // Without travel times the labs are the same, and the first one is kept
+    assert_eq!(pairs(&TravelTimes::default()), vec![vec![1, 2]]);
+    assert_eq!(pairs(&times()), vec![vec![1, 3]]);
+}
+
+#[test]
+fn tight_transfers_match_exhaustive_scoring() {
+    let priorities = Priorities {
+        tight_transfers: 1.0,
+        day_length: 0.1,
+        ..Default::default()
+    };
+    let travel = Travel::new(&times());
+
+    let bank = bank();
+    let includes = includes();
+    let classes = solver::include_classes(&bank, &includes, &Default::default());
+    let classes = solver::validate_linked_classes(classes, &[], &times());
+    let variants = solver::map_variants(&classes, &includes);
+
+    let mut exhaustive = Vec::new();
+    solver::bruteforce_schedules(
+        &variants[0].groups,
+        &mut Vec::new(),
+        &mut Vec::new(),
+        &mut |crns, schedule| {
+            let too_close = (0..schedule.len())
+                .any(|idx| travel.conflicts(schedule[idx], &schedule[idx + 1..]));
+            if too_close {
+                return;
+            }
+
+            let mut flat = Schedule::flatten(schedule);
+            flat.sort();
+            let picks = Picks {
+                tight_transfers: travel.tight_transfers(&flat.data()),
+                ..Default::default()
+            };
+            let (score, _) = priorities.score_picks(&flat.data(), &picks);
+            exhaustive.push((score, crns.to_vec()));
+        },
+    );
+    // Stable, so ties stay in search order
+    exhaustive.sort_by(|a, b| b.0.total_cmp(&a.0));
+    exhaustive.truncate(5);
+
+    let found = best(&bank, &plan(priorities), 5);
+    assert_eq!(found, exhaustive);
+    // Physics at 9 then calculus at 10 in the next building over is 10 minutes short
+    let (_, crns) = &found[0];
+    assert!(!(crns.contains(&1) && crns.contains(&4)));
+}
+
+#[test]
+fn travel_loads_from_plans() {
+    let plan = Plan::parse(
+        r#"
+        [priorities]
+        tight_transfers = 0.5
+
+        [travel]
+        building = 10
+        campus = 60
+        times = [
+            { from = "GS", to = "EE", minutes = 20 },
+            { from = "Jupiter", to = "Boca Raton", minutes = 45 },
+        ]
+        "#,
+        PlanFormat::Toml,
+    )
+    .unwrap();
+
+    assert_eq!(plan.priorities.tight_transfers, 0.5);
+    assert_eq!(plan.travel, times());
+}